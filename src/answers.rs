@@ -0,0 +1,265 @@
+use crate::{
+    input::InputProvider,
+    solution::{self, Part},
+};
+use anyhow::{anyhow, bail, Context};
+use itertools::Itertools;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    path::Path,
+    str::FromStr,
+};
+
+/// The input name that refers to [`InputProvider::bundled`].
+pub const BUNDLED_INPUT_NAME: &str = "bundled";
+
+/// The answers file for the inputs bundled with this crate's source.
+pub const BUNDLED_ANSWERS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/days/answers.txt");
+
+/// Identifies one answer: a part of a day, solved against a named set of inputs.
+///
+/// Ordered by day, then input, so that answers for the same input file sit next to each other.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AnswerKey {
+    pub day: u8,
+    pub input: String,
+    pub part: Part,
+}
+
+/// Known answers for any number of people's inputs, stored one per line as
+///
+/// ```text
+/// <day> <part> <input name> <answer>
+/// ```
+///
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Answers(BTreeMap<AnswerKey, String>);
+
+impl Answers {
+    pub fn get(&self, key: &AnswerKey) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// Returns the previous answer for `key`, if there was one.
+    pub fn insert(&mut self, key: AnswerKey, answer: String) -> Option<String> {
+        self.0.insert(key, answer)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&AnswerKey, &str)> {
+        self.0.iter().map(|(key, answer)| (key, answer.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromStr for Answers {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = Self::default();
+        s.lines()
+            .enumerate()
+            .map(|(idx, l)| (idx, l.trim()))
+            .filter(|(_idx, l)| !l.is_empty() && !l.starts_with('#'))
+            .try_for_each(|(idx, l)| {
+                let parse_line = || {
+                    let mut fields = l.split_whitespace();
+                    let mut next_field =
+                        |name| fields.next().with_context(|| anyhow!("missing {}", name));
+                    let day = next_field("day")?;
+                    let day = day
+                        .parse()
+                        .with_context(|| anyhow!("failed to parse {:?} as a day", day))?;
+                    let part = next_field("part")?.parse()?;
+                    let input = next_field("input name")?.to_owned();
+                    let answer = next_field("answer")?.to_owned();
+                    if let Some(unexpected) = fields.next() {
+                        bail!("unexpected {:?} after the answer", unexpected);
+                    }
+                    anyhow::Ok((AnswerKey { day, input, part }, answer))
+                };
+                let (key, answer) = parse_line()
+                    .with_context(|| anyhow!("answers file, line {}: {:?}", idx + 1, l))?;
+                match answers.insert(key, answer) {
+                    Some(_previous) => bail!("answers file, line {}: duplicate answer", idx + 1),
+                    None => Ok(()),
+                }
+            })?;
+        Ok(answers)
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|(key, answer)| {
+            writeln!(f, "{} {} {} {}", key.day, key.part, key.input, answer)
+        })
+    }
+}
+
+/// Resolves input names relative to `answers_dir`, the directory containing the answers file.
+pub fn input_provider(answers_dir: &Path, input_name: &str) -> InputProvider {
+    match input_name {
+        BUNDLED_INPUT_NAME => InputProvider::bundled(),
+        dir => InputProvider::new(answers_dir.join(dir)),
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Pass,
+    Mismatch {
+        actual: String,
+    },
+    /// Loading, parsing, or solving failed, with the rendered error.
+    Fail(String),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Verification {
+    pub key: AnswerKey,
+    pub expected: String,
+    pub outcome: Outcome,
+}
+
+impl Display for Verification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Self {
+            key,
+            expected,
+            outcome,
+        } = self;
+        write!(f, "day {} part {} [{}]: ", key.day, key.part, key.input)?;
+        match outcome {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Mismatch { actual } => {
+                write!(f, "mismatch: expected {}, got {}", expected, actual)
+            }
+            Outcome::Fail(error) => write!(f, "fail: {}", error),
+        }
+    }
+}
+
+/// Solves every answer in `answers` against the input `provider` gives for its input name,
+/// loading and parsing each input file only once.
+pub fn verify(answers: &Answers, provider: impl Fn(&str) -> InputProvider) -> Vec<Verification> {
+    let by_input_file = answers
+        .iter()
+        .group_by(|(key, _answer)| (key.day, key.input.clone()));
+    by_input_file
+        .into_iter()
+        .flat_map(|((day, input_name), answers)| {
+            let parsed = solution::find(day)
+                .with_context(|| anyhow!("no solution for day {}", day))
+                .and_then(|solution| {
+                    let input = provider(&input_name).load(day)?;
+                    let parsed = solution
+                        .parse(&input)
+                        .with_context(|| anyhow!("failed to parse input for day {}", day))?;
+                    Ok((solution, parsed))
+                })
+                .map_err(|e| format!("{:#}", e));
+            answers
+                .map(|(key, expected)| {
+                    let outcome = match &parsed {
+                        Ok((solution, parsed)) => match solution.solve(&**parsed, key.part) {
+                            Ok(actual) if actual == expected => Outcome::Pass,
+                            Ok(actual) => Outcome::Mismatch { actual },
+                            Err(e) => Outcome::Fail(format!("{:#}", e)),
+                        },
+                        Err(e) => Outcome::Fail(e.clone()),
+                    };
+                    Verification {
+                        key: key.clone(),
+                        expected: expected.to_owned(),
+                        outcome,
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    let raw = "\
+# comment
+
+7 2 alice 104149091
+1 1 bundled  1288
+";
+    let answers = raw.parse::<Answers>().unwrap();
+    assert_eq!(answers.len(), 2);
+    assert_eq!(
+        answers.get(&AnswerKey {
+            day: 7,
+            input: "alice".to_owned(),
+            part: Part::Two,
+        }),
+        Some("104149091")
+    );
+    assert_eq!(
+        answers.to_string(),
+        "1 1 bundled 1288\n7 2 alice 104149091\n"
+    );
+    assert_eq!(answers.to_string().parse::<Answers>().unwrap(), answers);
+
+    assert!("1 1 bundled".parse::<Answers>().is_err());
+    assert!("1 3 bundled 1288".parse::<Answers>().is_err());
+    assert!("1 1 bundled 1288 1311".parse::<Answers>().is_err());
+    assert!("1 1 bundled 1288\n1 1 bundled 1311"
+        .parse::<Answers>()
+        .is_err());
+}
+
+#[test]
+fn verifies_bundled_answers() {
+    let answers = std::fs::read_to_string(BUNDLED_ANSWERS_PATH)
+        .unwrap()
+        .parse::<Answers>()
+        .unwrap();
+    let answers_dir = Path::new(BUNDLED_ANSWERS_PATH).parent().unwrap();
+    let verifications = verify(&answers, |name| input_provider(answers_dir, name))
+        .into_iter()
+        .filter(|v| solution::find(v.key.day).is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        verifications.len(),
+        solution::SOLUTIONS.len() * Part::ALL.len()
+    );
+    verifications
+        .iter()
+        .for_each(|v| assert_eq!(v.outcome, Outcome::Pass, "{}", v));
+}
+
+#[test]
+#[cfg(all(feature = "day6", feature = "day7"))]
+fn reports_mismatches_and_failures() {
+    let answers = "\
+6 1 bundled 372984
+6 2 bundled 1
+7 1 nobody 364898
+"
+    .parse::<Answers>()
+    .unwrap();
+    let outcomes = verify(&answers, |name| input_provider(Path::new("/"), name))
+        .into_iter()
+        .map(|v| v.outcome)
+        .collect::<Vec<_>>();
+    assert_eq!(outcomes[0], Outcome::Pass);
+    assert_eq!(
+        outcomes[1],
+        Outcome::Mismatch {
+            actual: "1681503251694".to_owned()
+        }
+    );
+    assert!(matches!(outcomes[2], Outcome::Fail(_)));
+}
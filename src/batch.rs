@@ -0,0 +1,187 @@
+//! Running a single day's solution against every input in a directory, like the inputs of several
+//! puzzle accounts side by side.
+
+use crate::solution::{DynSolution, Part};
+use anyhow::{anyhow, Context};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// The outcome of solving one part of one input file. Each file is read and parsed once for all of
+/// its parts, so every part of a file shares the same `parse` time.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchResult {
+    pub path: PathBuf,
+    pub part: Part,
+    /// `None` if the file couldn't be read or parsed.
+    pub parse: Option<Duration>,
+    /// `None` if the file couldn't be read or parsed, or this part failed to solve.
+    pub solve: Option<Duration>,
+    /// The answer, or what went wrong reading, parsing, or solving.
+    pub answer: Result<String, String>,
+}
+
+/// Every file in `dir` that doesn't start with a `.`, sorted by name.
+pub fn input_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let context = || anyhow!("failed to list inputs in {}", dir.display());
+    let mut paths = fs::read_dir(dir)
+        .with_context(context)?
+        .filter_map(|res| {
+            res.map(|entry| {
+                let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                let path = entry.path();
+                (path.is_file() && !is_hidden).then_some(path)
+            })
+            .transpose()
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(context)?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Solves `parts` of every input in `dir` with `solution`. Failures are reported per file and part
+/// rather than stopping the batch; only failing to list `dir` is an error.
+pub fn run(
+    solution: &dyn DynSolution,
+    dir: &Path,
+    parts: &[Part],
+) -> anyhow::Result<Vec<BatchResult>> {
+    let mut results = Vec::new();
+    input_files(dir)?.into_iter().for_each(|path| {
+        let parsed = fs::read_to_string(&path)
+            .with_context(|| anyhow!("failed to read {}", path.display()))
+            .and_then(|input| {
+                // Only time parsing, not reading the file.
+                let start = Instant::now();
+                let parsed = solution.parse(&input)?;
+                Ok((parsed, start.elapsed()))
+            });
+
+        results.extend(parts.iter().map(|&part| {
+            let (parse, solve, answer) = match &parsed {
+                Ok((parsed, parse)) => {
+                    let start = Instant::now();
+                    match solution.solve(&**parsed, part) {
+                        Ok(answer) => (Some(*parse), Some(start.elapsed()), Ok(answer)),
+                        Err(e) => (Some(*parse), None, Err(format!("{:#}", e))),
+                    }
+                }
+                Err(e) => (None, None, Err(format!("{:#}", e))),
+            };
+            BatchResult {
+                path: path.clone(),
+                part,
+                parse,
+                solve,
+                answer,
+            }
+        }));
+    });
+    Ok(results)
+}
+
+/// Renders results as an aligned table with a row per file and part, naming files relative to the
+/// batch's directory.
+pub struct Table<'a>(pub &'a [BatchResult]);
+
+impl Display for Table<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let file_name = |result: &BatchResult| {
+            result.path.file_name().map_or_else(
+                || result.path.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            )
+        };
+        let time = |duration: Option<Duration>| {
+            duration.map_or_else(|| "-".to_owned(), |duration| format!("{:.1?}", duration))
+        };
+        let file_width = self
+            .0
+            .iter()
+            .map(|r| file_name(r).len())
+            .fold(4, usize::max);
+        let answer_width = self
+            .0
+            .iter()
+            .filter_map(|r| r.answer.as_ref().ok())
+            .map(String::len)
+            .fold(6, usize::max);
+
+        writeln!(
+            f,
+            "{:<file_width$} {:>4} {:>answer_width$} {:>12} {:>12}  error",
+            "file",
+            "part",
+            "answer",
+            "parse",
+            "solve",
+            file_width = file_width,
+            answer_width = answer_width,
+        )?;
+        self.0.iter().try_for_each(|result| {
+            write!(
+                f,
+                "{:<file_width$} {:>4} {:>answer_width$} {:>12} {:>12}",
+                file_name(result),
+                result.part,
+                result.answer.as_deref().unwrap_or("-"),
+                time(result.parse),
+                time(result.solve),
+                file_width = file_width,
+                answer_width = answer_width,
+            )?;
+            match &result.answer {
+                Ok(_) => writeln!(f),
+                Err(error) => writeln!(f, "  {}", error),
+            }
+        })
+    }
+}
+
+#[test]
+#[cfg(feature = "day6")]
+fn runs_every_input() {
+    let dir = std::env::temp_dir().join(format!("aoc2021-batch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("alice.txt"), "3,4,3,1,2\n").unwrap();
+    fs::write(dir.join("bob.txt"), "3,4,x\n").unwrap();
+    fs::write(dir.join(".hidden"), "").unwrap();
+
+    let solution = crate::solution::find(6).unwrap();
+    let results = run(solution, &dir, &Part::ALL);
+    fs::remove_dir_all(&dir).unwrap();
+    let results = results.unwrap();
+
+    let summary = results
+        .iter()
+        .map(|result| {
+            let file_name = result.path.file_name().unwrap().to_str().unwrap();
+            (file_name, result.part, result.answer.as_deref().ok())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("alice.txt", Part::One, Some("5934")),
+            ("alice.txt", Part::Two, Some("26984457539")),
+            ("bob.txt", Part::One, None),
+            ("bob.txt", Part::Two, None),
+        ]
+    );
+    assert!(results[0].parse.is_some() && results[0].solve.is_some());
+    assert!(results[2].parse.is_none() && results[2].solve.is_none());
+    assert!(results[2].answer.as_ref().unwrap_err().contains("column 5"));
+
+    let table = Table(&results).to_string();
+    let lines = table.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("file      part      answer"));
+    assert!(lines[2].starts_with("alice.txt    2 26984457539"));
+    assert!(lines[3].contains("  day 6 input, line 1, column 5: "));
+
+    assert!(run(solution, &dir, &Part::ALL).is_err());
+}
@@ -0,0 +1,249 @@
+use crate::{
+    memory::{self, Allocations},
+    solution::{DynSolution, Part},
+};
+use anyhow::{ensure, Context};
+use std::{
+    fmt::{self, Display, Formatter},
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+/// Summary of repeated measurements of a single phase.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Timings {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Timings {
+    /// Returns `None` if there are no `samples`.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Option<Self> {
+        samples.sort_unstable();
+        Some(Self {
+            min: *samples.first()?,
+            median: samples[samples.len() / 2],
+            max: *samples.last()?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Phase {
+    Parse,
+    Solve,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse => write!(f, "parse"),
+            Self::Solve => write!(f, "solve"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BenchResult {
+    pub day: u8,
+    pub part: Part,
+    pub iterations: usize,
+    pub parse: Timings,
+    pub solve: Timings,
+    /// The allocations made by the first parse, if they're being counted (see [`memory`]).
+    pub parse_allocations: Option<Allocations>,
+    /// The allocations made by the first solve, if they're being counted (see [`memory`]).
+    pub solve_allocations: Option<Allocations>,
+}
+
+impl BenchResult {
+    pub fn phases(&self) -> [(Phase, Timings, Option<Allocations>); 2] {
+        [
+            (Phase::Parse, self.parse, self.parse_allocations),
+            (Phase::Solve, self.solve, self.solve_allocations),
+        ]
+    }
+}
+
+/// Times parsing `input` and solving `part` of it separately, `iterations` times each, after an
+/// untimed run that counts each phase's allocations.
+pub fn bench(
+    solution: &dyn DynSolution,
+    part: Part,
+    input: &str,
+    iterations: usize,
+) -> anyhow::Result<BenchResult> {
+    ensure!(iterations > 0, "at least one iteration is required");
+
+    let mut parse_samples = Vec::with_capacity(iterations);
+    let mut solve_samples = Vec::with_capacity(iterations);
+    let (parsed, parse_allocations) = memory::measure(|| solution.parse(input));
+    let (solved, solve_allocations) = memory::measure(|| solution.solve(&*parsed?, part));
+    solved?;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let parsed = solution.parse(black_box(input))?;
+        parse_samples.push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(solution.solve(&*parsed, part)?);
+        solve_samples.push(start.elapsed());
+    }
+
+    Ok(BenchResult {
+        day: solution.day(),
+        part,
+        iterations,
+        parse: Timings::from_samples(parse_samples).context("no parse samples")?,
+        solve: Timings::from_samples(solve_samples).context("no solve samples")?,
+        parse_allocations,
+        solve_allocations,
+    })
+}
+
+/// Renders results as an aligned table for people to read.
+pub struct Table<'a>(pub &'a [BenchResult]);
+
+impl Display for Table<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let counting = self
+            .0
+            .iter()
+            .any(|result| result.parse_allocations.is_some());
+        write!(
+            f,
+            "{:>3} {:>4} {:>5} {:>12} {:>12} {:>12}",
+            "day", "part", "phase", "min", "median", "max"
+        )?;
+        if counting {
+            write!(f, " {:>8} {:>12} {:>12}", "allocs", "total", "peak")?;
+        }
+        writeln!(f)?;
+        self.0.iter().try_for_each(|result| {
+            result
+                .phases()
+                .iter()
+                .try_for_each(|(phase, timings, allocations)| {
+                    write!(
+                        f,
+                        "{:>3} {:>4} {:>5} {:>12} {:>12} {:>12}",
+                        result.day,
+                        result.part,
+                        phase,
+                        format!("{:.1?}", timings.min),
+                        format!("{:.1?}", timings.median),
+                        format!("{:.1?}", timings.max),
+                    )?;
+                    match allocations {
+                        Some(allocations) if counting => writeln!(
+                            f,
+                            " {:>8} {:>12} {:>12}",
+                            allocations.count,
+                            format!("{} B", allocations.total_bytes),
+                            format!("{} B", allocations.peak_bytes),
+                        ),
+                        _ => writeln!(f),
+                    }
+                })
+        })
+    }
+}
+
+/// Renders results as tab-separated values with times in nanoseconds, for comparing runs with
+/// other tools. The allocation columns are empty if allocations weren't counted.
+pub struct Tsv<'a>(pub &'a [BenchResult]);
+
+impl Display for Tsv<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "day\tpart\tphase\titerations\tmin_ns\tmedian_ns\tmax_ns\tallocations\ttotal_bytes\t\
+             peak_bytes"
+        )?;
+        self.0.iter().try_for_each(|result| {
+            result
+                .phases()
+                .iter()
+                .try_for_each(|(phase, timings, allocations)| {
+                    write!(
+                        f,
+                        "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                        result.day,
+                        result.part,
+                        phase,
+                        result.iterations,
+                        timings.min.as_nanos(),
+                        timings.median.as_nanos(),
+                        timings.max.as_nanos(),
+                    )?;
+                    match allocations {
+                        Some(allocations) => writeln!(
+                            f,
+                            "\t{}\t{}\t{}",
+                            allocations.count, allocations.total_bytes, allocations.peak_bytes
+                        ),
+                        None => writeln!(f, "\t\t\t"),
+                    }
+                })
+        })
+    }
+}
+
+#[test]
+fn timings_from_samples() {
+    let ms = Duration::from_millis;
+    assert_eq!(Timings::from_samples(Vec::new()), None);
+    assert_eq!(
+        Timings::from_samples(vec![ms(3), ms(1), ms(2)]),
+        Some(Timings {
+            min: ms(1),
+            median: ms(2),
+            max: ms(3),
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "day6")]
+fn bench_and_render() {
+    let solution = crate::solution::find(6).unwrap();
+    let result = bench(solution, Part::Two, "3,4,3,1,2", 3).unwrap();
+    assert_eq!(
+        (result.day, result.part, result.iterations),
+        (6, Part::Two, 3)
+    );
+    result
+        .phases()
+        .iter()
+        .for_each(|(_phase, timings, _allocations)| {
+            assert!(timings.min <= timings.median && timings.median <= timings.max)
+        });
+    // Tests run with the counting allocator, and parsing collects the fish into a `Vec`.
+    assert!(result.parse_allocations.unwrap().count > 0);
+    assert!(result.solve_allocations.is_some());
+
+    let results = [result];
+    let tsv = Tsv(&results).to_string();
+    let mut lines = tsv.lines();
+    assert_eq!(
+        lines.next(),
+        Some(
+            "day\tpart\tphase\titerations\tmin_ns\tmedian_ns\tmax_ns\tallocations\t\
+             total_bytes\tpeak_bytes"
+        )
+    );
+    assert!(lines.next().unwrap().starts_with("6\t2\tparse\t3\t"));
+    assert!(lines.next().unwrap().starts_with("6\t2\tsolve\t3\t"));
+    assert_eq!(lines.next(), None);
+
+    let table = Table(&results).to_string();
+    assert_eq!(table.lines().count(), 3);
+    assert!(table
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("  allocs        total         peak"));
+
+    assert!(bench(solution, Part::One, "3,4,3,1,2", 0).is_err());
+}
@@ -0,0 +1,503 @@
+#[cfg(feature = "count-allocations")]
+use advent_of_code_2021::memory::CountingAllocator;
+use advent_of_code_2021::{
+    answers::{self, Answers, Outcome},
+    batch,
+    bench::{self, BenchResult, Table, Tsv},
+    diagnostics::Diagnostic,
+    error::ParseError,
+    generate,
+    input::{self, InputProvider, INPUT_DIR_ENV_VAR},
+    solution::{self, DynSolution, Part, SOLUTIONS},
+    trace::{self, HumanTrace, JsonLines},
+};
+use anyhow::{anyhow, bail, Context};
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+const USAGE: &str = "\
+usage: aoc2021 [--json] [--trace FORMAT] <DAY|all> [PART|all] [INPUT]
+       aoc2021 --bench ITERATIONS [--tsv] <DAY|all> [PART|all] [INPUT]
+       aoc2021 --verify ANSWERS [DAY|all] [PART|all]
+       aoc2021 --generate SIZE [--seed SEED] <DAY>
+       aoc2021 --batch DIR <DAY> [PART|all]
+
+  DAY    day to run, or `all` to run every day
+  PART   `1`, `2`, or `all` (default: `all`); can be left out before INPUT,
+         unless INPUT is itself named `1`, `2`, or `all`
+  INPUT  path to the puzzle input, or `-` for stdin; when DAY is `all`, a
+         directory containing `day<N>_input.txt` for each day
+
+When INPUT is omitted, `day<N>_input.txt` is loaded from the directory named by
+`AOC2021_INPUT_DIR`, or from the inputs bundled with the source if it's unset.
+
+  --json              print each answer as a JSON object on its own line, along
+                      with the intermediate results behind it (winning boards,
+                      dangerous points, alignment positions, ...)
+  --trace FORMAT      also report what the solvers do along the way (drawn
+                      numbers, eliminated candidates, simulated days, ...) to
+                      stderr, as `human`-readable lines or `json` lines
+  --bench ITERATIONS  instead of printing answers, time parsing and solving
+                      each selected part ITERATIONS times and report the
+                      min/median/max of each phase (and, if built with the
+                      `count-allocations` feature, how many allocations it
+                      makes, the bytes they total, and its peak bytes in use)
+  --tsv               report benchmarks as tab-separated values in nanoseconds
+  --verify ANSWERS    solve every input listed in the answers file ANSWERS
+                      (optionally only for DAY and PART) and report whether
+                      each answer passes, mismatches, or fails to be solved;
+                      the bundled inputs' answers are in `src/days/answers.txt`
+  --generate SIZE     print a random, valid input for DAY with SIZE lines (or
+                      boards, fish, or crabs, depending on the day)
+  --seed SEED         seed for `--generate` (default: 0)
+  --batch DIR         solve every file in the directory DIR as an input for DAY
+                      (e.g., one per puzzle account) and report each answer,
+                      its parse and solve times, or what went wrong
+";
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+enum Selection<T> {
+    All,
+    One(T),
+}
+
+impl<T> Selection<T> {
+    fn parse(raw: &str, parse_one: impl FnOnce(&str) -> anyhow::Result<T>) -> anyhow::Result<Self> {
+        match raw {
+            "all" => Ok(Self::All),
+            raw => parse_one(raw).map(Self::One),
+        }
+    }
+}
+
+enum Input {
+    Provided,
+    Stdin,
+    Path(PathBuf),
+}
+
+#[derive(Clone, Copy)]
+enum TraceFormat {
+    Human,
+    Json,
+}
+
+enum Mode {
+    Solve {
+        json: bool,
+        trace: Option<TraceFormat>,
+    },
+    Bench {
+        iterations: usize,
+        tsv: bool,
+    },
+    Verify {
+        answers: PathBuf,
+    },
+    Generate {
+        size: usize,
+        seed: u64,
+    },
+    Batch {
+        dir: PathBuf,
+    },
+}
+
+struct Args {
+    day: Selection<&'static dyn DynSolution>,
+    part: Selection<Part>,
+    input: Input,
+    mode: Mode,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut bench_iterations = None;
+        let mut tsv = false;
+        let mut verify = None;
+        let mut generate_size = None;
+        let mut seed = None;
+        let mut trace = None;
+        let mut json = false;
+        let mut batch = None;
+        let mut positional = Vec::new();
+        while let Some(arg) = args.next() {
+            match &*arg {
+                "--bench" => {
+                    let raw = args
+                        .next()
+                        .context("`--bench` needs a number of iterations")?;
+                    let iterations = raw
+                        .parse()
+                        .with_context(|| anyhow!("failed to parse {:?} as iterations", raw))?;
+                    bench_iterations = Some(iterations);
+                }
+                "--tsv" => tsv = true,
+                "--verify" => {
+                    let answers = args.next().context("`--verify` needs an answers file")?;
+                    verify = Some(PathBuf::from(answers));
+                }
+                "--generate" => {
+                    let raw = args.next().context("`--generate` needs a size")?;
+                    let size = raw
+                        .parse()
+                        .with_context(|| anyhow!("failed to parse {:?} as a size", raw))?;
+                    generate_size = Some(size);
+                }
+                "--seed" => {
+                    let raw = args.next().context("`--seed` needs a number")?;
+                    let parsed = raw
+                        .parse()
+                        .with_context(|| anyhow!("failed to parse {:?} as a seed", raw))?;
+                    seed = Some(parsed);
+                }
+                "--batch" => {
+                    let dir = args.next().context("`--batch` needs a directory")?;
+                    batch = Some(PathBuf::from(dir));
+                }
+                "--json" => json = true,
+                "--trace" => {
+                    let raw = args.next().context("`--trace` needs a format")?;
+                    trace = Some(match &*raw {
+                        "human" => TraceFormat::Human,
+                        "json" => TraceFormat::Json,
+                        _ => bail!("unknown trace format {:?}, expected `human` or `json`", raw),
+                    });
+                }
+                _ => positional.push(arg),
+            }
+        }
+        let mode = match (bench_iterations, verify, generate_size, batch) {
+            (Some(iterations), None, None, None) => Mode::Bench { iterations, tsv },
+            (None, Some(answers), None, None) => Mode::Verify { answers },
+            (None, None, Some(size), None) => Mode::Generate {
+                size,
+                seed: seed.unwrap_or(0),
+            },
+            (None, None, None, Some(dir)) => Mode::Batch { dir },
+            (None, None, None, None) => Mode::Solve { json, trace },
+            _ => {
+                bail!("only one of `--bench`, `--verify`, `--generate`, and `--batch` can be used")
+            }
+        };
+        if tsv && !matches!(mode, Mode::Bench { .. }) {
+            bail!("`--tsv` only applies to `--bench`");
+        }
+        if seed.is_some() && !matches!(mode, Mode::Generate { .. }) {
+            bail!("`--seed` only applies to `--generate`");
+        }
+        if json && !matches!(mode, Mode::Solve { .. }) {
+            bail!("`--json` only applies when printing answers");
+        }
+        if trace.is_some() && !matches!(mode, Mode::Solve { .. }) {
+            bail!("`--trace` only applies when printing answers");
+        }
+
+        let mut args = positional.into_iter();
+        let day = match (args.next(), &mode) {
+            (Some(day), _) => Selection::parse(&day, |raw| {
+                let day = raw
+                    .parse()
+                    .with_context(|| anyhow!("failed to parse {:?} as a day", raw))?;
+                solution::find(day).with_context(|| {
+                    anyhow!(
+                        "no solution for day {} (is its `day{}` feature enabled?)",
+                        day,
+                        day
+                    )
+                })
+            })?,
+            (None, Mode::Verify { .. }) => Selection::All,
+            (None, _) => bail!("no day specified"),
+        };
+        // `DAY INPUT` leaves out PART, as long as INPUT can't be mistaken for one.
+        let mut rest = args.collect::<Vec<_>>();
+        let takes_input = matches!(mode, Mode::Solve { .. } | Mode::Bench { .. });
+        if takes_input && rest.len() == 1 && Selection::parse(&rest[0], str::parse::<Part>).is_err()
+        {
+            rest.insert(0, "all".to_owned());
+        }
+        let mut args = rest.into_iter();
+        let part = match args.next() {
+            Some(_) if matches!(mode, Mode::Generate { .. }) => {
+                bail!("`--generate` generates inputs for both parts")
+            }
+            Some(part) => Selection::parse(&part, str::parse)?,
+            None => Selection::All,
+        };
+        let input = match args.next() {
+            None => Input::Provided,
+            Some(_) if matches!(mode, Mode::Verify { .. }) => {
+                bail!("`--verify` reads inputs from the answers file")
+            }
+            Some(_) if matches!(mode, Mode::Batch { .. }) => {
+                bail!("`--batch` reads every input in its directory")
+            }
+            Some(path) if path == "-" => Input::Stdin,
+            Some(path) => Input::Path(path.into()),
+        };
+        if let Some(unexpected) = args.next() {
+            bail!("unexpected argument {:?}", unexpected);
+        }
+
+        Ok(Self {
+            day,
+            part,
+            input,
+            mode,
+        })
+    }
+}
+
+fn read_input(path: Option<&Path>) -> anyhow::Result<String> {
+    match path {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| anyhow!("failed to read input from {}", path.display())),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .context("failed to read input from stdin")?;
+            Ok(input)
+        }
+    }
+}
+
+/// What [`run_day`] does with each part.
+#[derive(Clone, Copy)]
+enum Action {
+    Print,
+    PrintJson,
+    Bench { iterations: usize },
+}
+
+/// Prints the answer to each of `parts`, or, for [`Action::Bench`], benchmarks each of them
+/// instead and returns the results.
+fn run_day(
+    solution: &dyn DynSolution,
+    parts: &[Part],
+    input: &str,
+    source_name: &str,
+    action: Action,
+) -> anyhow::Result<Vec<BenchResult>> {
+    let day = solution.day();
+    // Parsing normalizes the input too, but errors point into the normalized input.
+    let input = input::normalize(input);
+    let parsed = solution
+        .parse(&input)
+        .inspect_err(|e| {
+            if let Some(parse_error) = e.downcast_ref::<ParseError>() {
+                eprint!(
+                    "{}",
+                    Diagnostic::new(&input, parse_error).with_source_name(source_name)
+                );
+            }
+        })
+        .with_context(|| anyhow!("failed to parse input for day {}", day))?;
+    if let Action::Bench { iterations } = action {
+        return parts
+            .iter()
+            .map(|&part| {
+                bench::bench(solution, part, &input, iterations)
+                    .with_context(|| anyhow!("failed to benchmark day {} part {}", day, part))
+            })
+            .collect();
+    }
+    parts.iter().try_for_each(|&part| {
+        let context = || anyhow!("failed to solve day {} part {}", day, part);
+        if let Action::PrintJson = action {
+            let report = solution.report(&*parsed, part).with_context(context)?;
+            println!("{}", report);
+        } else {
+            let answer = solution.solve(&*parsed, part).with_context(context)?;
+            println!("day {} part {}: {}", day, part, answer);
+        }
+        anyhow::Ok(())
+    })?;
+    Ok(Vec::new())
+}
+
+/// Checks the answers in `answers_path` for `day` and `parts`, printing one line per answer and
+/// failing unless every one of them passes.
+fn verify(
+    answers_path: &Path,
+    day: &Selection<&'static dyn DynSolution>,
+    parts: &[Part],
+) -> anyhow::Result<()> {
+    let answers = fs::read_to_string(answers_path)
+        .with_context(|| anyhow!("failed to read answers from {}", answers_path.display()))?
+        .parse::<Answers>()?;
+    let answers = answers
+        .iter()
+        .filter(|(key, _answer)| match day {
+            Selection::All => true,
+            Selection::One(solution) => key.day == solution.day(),
+        })
+        .filter(|(key, _answer)| parts.contains(&key.part))
+        .fold(Answers::default(), |mut answers, (key, answer)| {
+            answers.insert(key.clone(), answer.to_owned());
+            answers
+        });
+    let answers_dir = answers_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let (mut passed, mut mismatched, mut failed) = (0, 0, 0);
+    answers::verify(&answers, |name| answers::input_provider(answers_dir, name))
+        .iter()
+        .for_each(|verification| {
+            println!("{}", verification);
+            match verification.outcome {
+                Outcome::Pass => passed += 1,
+                Outcome::Mismatch { .. } => mismatched += 1,
+                Outcome::Fail(_) => failed += 1,
+            }
+        });
+    println!(
+        "{} passed, {} mismatched, {} failed",
+        passed, mismatched, failed
+    );
+    if mismatched + failed > 0 {
+        bail!(
+            "{} of {} answers did not pass",
+            mismatched + failed,
+            answers.len()
+        );
+    }
+    Ok(())
+}
+
+/// Solves `parts` of every input in `dir`, printing a table with a row per file and part and
+/// failing unless every one of them was solved.
+fn run_batch(solution: &dyn DynSolution, dir: &Path, parts: &[Part]) -> anyhow::Result<()> {
+    let results = batch::run(solution, dir, parts)?;
+    if results.is_empty() {
+        bail!("no inputs in {}", dir.display());
+    }
+    print!("{}", batch::Table(&results));
+    let failed = results
+        .iter()
+        .filter(|result| result.answer.is_err())
+        .count();
+    if failed > 0 {
+        bail!("{} of {} runs failed", failed, results.len());
+    }
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let Args {
+        day,
+        part,
+        input,
+        mode,
+    } = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprint!("{}", USAGE);
+            return Err(e);
+        }
+    };
+
+    let parts = match part {
+        Selection::All => Part::ALL.to_vec(),
+        Selection::One(part) => vec![part],
+    };
+
+    match (&mode, &day) {
+        (Mode::Verify { answers }, _) => return verify(answers, &day, &parts),
+        (&Mode::Generate { size, seed }, Selection::One(solution)) => {
+            let input = generate::generate(solution.day(), seed, size)
+                .with_context(|| anyhow!("no generator for day {}", solution.day()))?;
+            print!("{}", input);
+            return Ok(());
+        }
+        (Mode::Generate { .. }, Selection::All) => bail!("`--generate` needs a single day"),
+        (Mode::Batch { dir }, Selection::One(solution)) => {
+            return run_batch(*solution, dir, &parts)
+        }
+        (Mode::Batch { .. }, Selection::All) => bail!("`--batch` needs a single day"),
+        (Mode::Solve { .. } | Mode::Bench { .. }, _) => (),
+    }
+
+    let provider = || {
+        let provider = InputProvider::from_env();
+        if env::var_os(INPUT_DIR_ENV_VAR).is_none() {
+            eprintln!(
+                "note: using bundled inputs from {}",
+                provider.dir().display()
+            );
+        }
+        provider
+    };
+
+    let action = match mode {
+        Mode::Solve { json: false, .. } => Action::Print,
+        Mode::Solve { json: true, .. } => Action::PrintJson,
+        Mode::Bench { iterations, .. } => Action::Bench { iterations },
+        Mode::Verify { .. } | Mode::Generate { .. } | Mode::Batch { .. } => {
+            unreachable!("handled above")
+        }
+    };
+
+    let run = || match day {
+        Selection::One(solution) => {
+            let (input, source_name) = match input {
+                Input::Provided => {
+                    let provider = provider();
+                    (
+                        provider.load(solution.day())?,
+                        provider.path(solution.day()).display().to_string(),
+                    )
+                }
+                Input::Stdin => (read_input(None)?, "<stdin>".to_owned()),
+                Input::Path(path) => (read_input(Some(&path))?, path.display().to_string()),
+            };
+            run_day(solution, &parts, &input, &source_name, action)
+        }
+        Selection::All => {
+            let provider = match input {
+                Input::Provided => provider(),
+                Input::Stdin => bail!("cannot read every day's input from stdin"),
+                Input::Path(dir) => InputProvider::new(dir),
+            };
+            let mut bench_results = Vec::new();
+            SOLUTIONS.iter().try_for_each(|solution| {
+                let source_name = provider.path(solution.day()).display().to_string();
+                run_day(
+                    *solution,
+                    &parts,
+                    &provider.load(solution.day())?,
+                    &source_name,
+                    action,
+                )
+                .map(|results| bench_results.extend(results))
+            })?;
+            Ok(bench_results)
+        }
+    };
+    let bench_results = match mode {
+        Mode::Solve {
+            trace: Some(TraceFormat::Human),
+            ..
+        } => trace::observe(HumanTrace(io::stderr()), run),
+        Mode::Solve {
+            trace: Some(TraceFormat::Json),
+            ..
+        } => trace::observe(JsonLines(io::stderr()), run),
+        _ => run(),
+    }?;
+
+    if let Mode::Bench { tsv, .. } = mode {
+        if tsv {
+            print!("{}", Tsv(&bench_results));
+        } else {
+            print!("{}", Table(&bench_results));
+        }
+    }
+    Ok(())
+}
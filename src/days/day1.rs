@@ -1,32 +1,136 @@
-use anyhow::{anyhow, Context};
-use std::str::FromStr;
+use crate::{
+    error::{ParseError, ReadError},
+    json::{Json, ToJson},
+    parse::{read_records, Source},
+    solution::{Part, Solution},
+};
+use anyhow::ensure;
+use itertools::process_results;
+use std::{
+    fmt::{Debug, Display},
+    io::BufRead,
+    ops::{Add, Sub},
+    str::FromStr,
+};
 
-fn parse_measurements(
+pub mod anomalies;
+pub mod trend;
+
+/// An integer type that depths can be measured in. The puzzle's depths fit in a `u16`, but other
+/// sonar sweeps may need a wider or signed type ([`Solver`] uses `u32`s).
+pub trait Depth: Copy + Ord + FromStr + Display + Debug {
+    /// A type wider than the depths, for summing windows of them without overflowing (unless the
+    /// windows are longer than [`Depth::max_window_size`]).
+    type Sum: Copy
+        + Ord
+        + Display
+        + Debug
+        + Default
+        + From<Self>
+        + Add<Output = Self::Sum>
+        + Sub<Output = Self::Sum>;
+
+    /// Describes the valid depths, like "a depth from 0 to 65535".
+    fn expected() -> String;
+
+    /// The most depths that can be summed without overflowing [`Depth::Sum`].
+    fn max_window_size() -> usize;
+
+    /// How far apart two depths are, which might not fit in `Self` (e.g., for `i64`s).
+    fn abs_diff(self, other: Self) -> Self::Sum;
+}
+
+macro_rules! impl_depth {
+    ($($depth:ty => $sum:ty),*) => {
+        $(
+            impl Depth for $depth {
+                type Sum = $sum;
+
+                fn expected() -> String {
+                    format!("a depth from {} to {}", <$depth>::MIN, <$depth>::MAX)
+                }
+
+                fn max_window_size() -> usize {
+                    let max = <$sum>::MAX / <$sum>::from(<$depth>::MAX);
+                    usize::try_from(max).unwrap_or(usize::MAX)
+                }
+
+                fn abs_diff(self, other: Self) -> Self::Sum {
+                    let (a, b) = (Self::Sum::from(self), Self::Sum::from(other));
+                    if a > b {
+                        a - b
+                    } else {
+                        b - a
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_depth!(u16 => u32, u32 => u64, u64 => u128, i64 => i128);
+
+fn parse_measurement<T>(source: Source, l: &str) -> Result<T, ParseError>
+where
+    T: Depth,
+{
+    source.parse(l, T::expected())
+}
+
+pub fn parse_measurements<T>(
     input: &str,
-) -> impl Iterator<Item = (usize, Result<u16, <u16 as FromStr>::Err>)> + '_ {
-    input
-        .lines()
-        .enumerate()
-        .map(|(idx, l)| (idx, l.trim()))
-        .filter(|(_idx, l)| !l.is_empty())
-        .map(|(idx, l)| (idx, l.parse()))
+) -> impl Iterator<Item = (usize, Result<T, ParseError>)> + '_
+where
+    T: Depth,
+{
+    let source = Source::new(1, input);
+    source
+        .records()
+        .map(move |(idx, l)| (idx, parse_measurement(source, l)))
 }
 
-fn iter_increasing_measurements(input: &str) -> impl Iterator<Item = (usize, u16)> + '_ {
-    let mut measurements = parse_measurements(input).map(|(idx, res)| {
-        (
-            idx,
-            res.with_context(|| anyhow!("line {} sux", idx)).unwrap(),
-        )
-    });
-    let (_idx, mut last) = measurements.next().context("y u no measurements").unwrap();
+/// Like [`parse_measurements`], but reads measurements one line at a time from `reader`.
+pub fn read_measurements<T>(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(usize, T), ReadError>>
+where
+    T: Depth,
+{
+    read_records(reader).map(|res| {
+        let (idx, line) = res?;
+        parse_measurement(Source::new(1, &line), line.trim())
+            .map(|measurement| (idx, measurement))
+            .map_err(|e| e.on_line(idx).into())
+    })
+}
+
+/// Collects [`parse_measurements`] into line-indexed measurements, failing on the first line that
+/// isn't a valid measurement.
+pub fn collect_measurements<T>(input: &str) -> Result<Vec<(usize, T)>, ParseError>
+where
+    T: Depth,
+{
+    parse_measurements(input)
+        .map(|(idx, res)| res.map(|measurement| (idx, measurement)))
+        .collect()
+}
+
+pub fn iter_increasing_measurements<T>(
+    measurements: impl IntoIterator<Item = (usize, T)>,
+) -> impl Iterator<Item = (usize, T)>
+where
+    T: Depth,
+{
+    let mut measurements = measurements.into_iter();
+    let mut last = measurements.next().map(|(_idx, first)| first);
     measurements.filter(move |&(_idx, next)| {
-        let is_increasing = next > last;
-        last = next;
+        let is_increasing = last.is_some_and(|last| next > last);
+        last = Some(next);
         is_increasing
     })
 }
 
+#[cfg(test)]
 const EXAMPLE: &str = "\
 199
 200
@@ -43,7 +147,8 @@ const EXAMPLE: &str = "\
 #[test]
 fn part_1_example() {
     assert_eq!(
-        iter_increasing_measurements(EXAMPLE).collect::<Vec<_>>(),
+        iter_increasing_measurements(collect_measurements::<u16>(EXAMPLE).unwrap())
+            .collect::<Vec<_>>(),
         &[
             (1, 200),
             (2, 208),
@@ -56,66 +161,372 @@ fn part_1_example() {
     );
 }
 
-const INPUT: &str = include_str!("day1_part1.txt");
+#[test]
+fn parse_errors() {
+    let errors = parse_measurements::<u16>("199\n\n  2OO\n70000\n")
+        .filter_map(|(idx, res)| res.err().map(|e| (idx, e.line, e.column, e.found)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [(2, 3, 3, "2OO".to_owned()), (3, 4, 1, "70000".to_owned())]
+    );
+}
+
+#[test]
+fn messy_input() {
+    use crate::input::{messy, normalize};
+
+    let messy = messy(EXAMPLE);
+    assert_eq!(
+        Solver::parse(&normalize(&messy)).unwrap(),
+        Solver::parse(EXAMPLE).unwrap()
+    );
+    assert_eq!(solve_part1_from_reader::<u16>(messy.as_bytes()).unwrap(), 7);
+    assert_eq!(solve_part2_from_reader::<u16>(messy.as_bytes()).unwrap(), 5);
+}
+
+#[cfg(test)]
+const INPUT: &str = include_str!("day1_input.txt");
 
 #[test]
 fn part_1() {
-    assert_eq!(iter_increasing_measurements(INPUT).count(), 1288);
+    assert_eq!(
+        iter_increasing_measurements(collect_measurements::<u16>(INPUT).unwrap()).count(),
+        1288
+    );
 }
 
-fn iter_increasing_3_window_sums(input: &str) -> impl Iterator<Item = (usize, u16)> + '_ {
-    let measurements = parse_measurements(input)
-        .map(|(idx, res)| {
-            (
-                idx,
-                res.with_context(|| anyhow!("line {} sux", idx)).unwrap(),
-            )
-        })
-        .collect::<Vec<_>>();
+/// Yields the windows of `window_size` consecutive measurements whose sums are greater than the
+/// previous window's, along with the line index of each window's first measurement and its sum.
+/// With a `window_size` of 1, this yields the same as [`iter_increasing_measurements`].
+///
+/// Fails if windows of `window_size` measurements could overflow [`Depth::Sum`].
+pub fn iter_increasing_window_sums<T>(
+    measurements: &[(usize, T)],
+    window_size: usize,
+) -> anyhow::Result<impl Iterator<Item = (usize, T::Sum)> + '_>
+where
+    T: Depth,
+{
+    iter_increasing_windows(measurements.iter().copied(), window_size)
+}
 
-    let mut windows = measurements.windows(3);
+/// Like [`iter_increasing_window_sums`], but for any iterator of measurements, which may be an
+/// unbounded stream. Only a single window of measurements is held in memory at a time.
+///
+/// Consecutive windows share all but their first and last measurements, so rather than summing
+/// every window, this compares the measurement entering each window with the one leaving it, and
+/// keeps a running sum.
+pub fn iter_increasing_windows<I, T>(
+    measurements: I,
+    window_size: usize,
+) -> anyhow::Result<IncreasingWindows<I::IntoIter, T>>
+where
+    I: IntoIterator<Item = (usize, T)>,
+    T: Depth,
+{
+    ensure!(
+        window_size > 0,
+        "windows must contain at least one measurement"
+    );
+    ensure!(
+        window_size <= T::max_window_size(),
+        "sums of windows of {} measurements could overflow, at most {} fit",
+        window_size,
+        T::max_window_size()
+    );
+    Ok(IncreasingWindows {
+        measurements: measurements.into_iter(),
+        window: Vec::with_capacity(window_size),
+        window_size,
+        oldest: 0,
+        sum: T::Sum::default(),
+    })
+}
 
-    let calc_sum = |&[(window_start_idx, n_1), (_, n_2), (_, n_3)]: &[(usize, u16); 3]| {
-        let context = || anyhow!("ugh, addition of window {} blew up", window_start_idx);
-        (
-            window_start_idx,
-            n_1.checked_add(n_2)
-                .with_context(context)
-                .unwrap()
-                .checked_add(n_3)
-                .with_context(context)
-                .unwrap(),
-        )
-    };
+/// See [`iter_increasing_windows`].
+pub struct IncreasingWindows<I, T>
+where
+    T: Depth,
+{
+    measurements: I,
+    /// A ring buffer of the current window's measurements, once it's filled up.
+    window: Vec<(usize, T)>,
+    window_size: usize,
+    /// The position of the window's first measurement in `window`.
+    oldest: usize,
+    /// The sum of the measurements in `window`.
+    sum: T::Sum,
+}
 
-    let (_idx, mut last_sum) = calc_sum(
-        windows
-            .next()
-            .context("y u no measurements window")
-            .unwrap()
-            .try_into()
-            .unwrap(),
-    );
-    windows
-        .filter_map(move |window| {
-            let (idx, sum) = calc_sum(window.try_into().unwrap());
-            let ret = (sum > last_sum).then(|| (idx, sum));
-            last_sum = sum;
-            ret
-        })
-        .collect::<Vec<_>>()
-        .into_iter()
+impl<I, T> Iterator for IncreasingWindows<I, T>
+where
+    I: Iterator<Item = (usize, T)>,
+    T: Depth,
+{
+    type Item = (usize, T::Sum);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.window.len() < self.window_size {
+            let (idx, measurement) = self.measurements.next()?;
+            self.window.push((idx, measurement));
+            self.sum = self.sum + measurement.into();
+        }
+        loop {
+            let (idx, entering) = self.measurements.next()?;
+            let (_idx, leaving) = std::mem::replace(&mut self.window[self.oldest], (idx, entering));
+            self.oldest = (self.oldest + 1) % self.window_size;
+            // Subtracting first keeps the running sum within the sum of a window.
+            self.sum = self.sum - leaving.into() + entering.into();
+            if entering > leaving {
+                let (window_start_idx, _) = self.window[self.oldest];
+                return Some((window_start_idx, self.sum));
+            }
+        }
+    }
+}
+
+pub fn iter_increasing_3_window_sums<T>(
+    measurements: &[(usize, T)],
+) -> anyhow::Result<impl Iterator<Item = (usize, T::Sum)> + '_>
+where
+    T: Depth,
+{
+    iter_increasing_window_sums(measurements, 3)
 }
 
 #[test]
 fn part_2_example() {
     assert_eq!(
-        iter_increasing_3_window_sums(EXAMPLE).collect::<Vec<_>>(),
+        iter_increasing_3_window_sums(&collect_measurements::<u16>(EXAMPLE).unwrap())
+            .unwrap()
+            .collect::<Vec<_>>(),
         &[(1, 618), (4, 647), (5, 716), (6, 769), (7, 792)],
     )
 }
 
 #[test]
 fn part_2() {
-    assert_eq!(iter_increasing_3_window_sums(INPUT).count(), 1311);
+    assert_eq!(
+        iter_increasing_3_window_sums(&collect_measurements::<u16>(INPUT).unwrap())
+            .unwrap()
+            .count(),
+        1311
+    );
+}
+
+#[test]
+fn window_sizes() {
+    let measurements = collect_measurements::<u16>(EXAMPLE).unwrap();
+    let increases = |window_size| {
+        iter_increasing_window_sums(&measurements, window_size)
+            .unwrap()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        increases(1),
+        iter_increasing_measurements(measurements.iter().copied())
+            .map(|(idx, depth)| (idx, u32::from(depth)))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        increases(3),
+        [(1, 618), (4, 647), (5, 716), (6, 769), (7, 792)]
+    );
+    assert_eq!(increases(9), [(1, 2057)]);
+    assert_eq!(increases(10), []);
+    assert_eq!(increases(11), []);
+    assert!(iter_increasing_window_sums(&measurements, 0).is_err());
+}
+
+#[test]
+fn streaming_windows() {
+    let measurements = collect_measurements::<u16>(INPUT).unwrap();
+    (1..=10).chain([2000, 2001]).for_each(|window_size| {
+        let streamed = iter_increasing_windows(measurements.iter().copied(), window_size)
+            .unwrap()
+            .collect::<Vec<_>>();
+        // Sum every window the slow way.
+        let sums = measurements
+            .windows(window_size)
+            .map(|window| {
+                let sum = window.iter().map(|&(_idx, depth)| u32::from(depth)).sum();
+                (window[0].0, sum)
+            })
+            .collect::<Vec<_>>();
+        let summed = sums
+            .windows(2)
+            .filter(|pair| pair[1].1 > pair[0].1)
+            .map(|pair| pair[1])
+            .collect::<Vec<_>>();
+        assert_eq!(streamed, summed, "window size {}", window_size);
+    });
+
+    // Measurements don't need to end, and sums are wider than the depths.
+    let sawtooth = || (0..).map(|idx| (idx, 65_000 + (idx % 500) as u16));
+    assert_eq!(
+        iter_increasing_windows(sawtooth(), 3).unwrap().nth(1000),
+        Some((1007, 195_024))
+    );
+    assert!(iter_increasing_windows(sawtooth(), 0).is_err());
+    assert!(iter_increasing_windows(sawtooth(), 65_538).is_err());
+}
+
+#[test]
+fn depth_types() {
+    let sweep = "70000\n-5\n3000000000\n-1\n";
+    let error = collect_measurements::<u32>(sweep).unwrap_err();
+    assert_eq!(
+        (error.line, &*error.expected),
+        (2, "a depth from 0 to 4294967295")
+    );
+    let error = collect_measurements::<u64>(sweep).unwrap_err();
+    assert_eq!(
+        (error.line, &*error.expected),
+        (2, "a depth from 0 to 18446744073709551615")
+    );
+    let measurements = collect_measurements::<i64>(sweep).unwrap();
+    assert_eq!(
+        iter_increasing_measurements(measurements.iter().copied()).collect::<Vec<_>>(),
+        [(2, 3_000_000_000)]
+    );
+    assert_eq!(
+        iter_increasing_window_sums(&measurements, 2)
+            .unwrap()
+            .collect::<Vec<_>>(),
+        [(1, 2_999_999_995), (2, 2_999_999_999)]
+    );
+    assert_eq!(solve_part1(&measurements).unwrap(), 1);
+    assert_eq!(solve_part2(&measurements).unwrap(), 0);
+
+    // Sums are accumulated in a wider type, so windows of the widest depths don't overflow...
+    let deep = [(0, u64::MAX), (1, u64::MAX), (2, u64::MAX), (3, u64::MAX)];
+    assert_eq!(iter_increasing_window_sums(&deep, 3).unwrap().count(), 0);
+    // ...unless they're so large that even the wider type overflows, which is an error.
+    let deep = (0..65_539).map(|idx| (idx, u16::MAX)).collect::<Vec<_>>();
+    assert!(iter_increasing_window_sums(&deep, 65_537).is_ok());
+    assert!(iter_increasing_window_sums(&deep, 65_538).is_err());
+}
+
+pub fn solve_part1<T>(measurements: &[(usize, T)]) -> anyhow::Result<usize>
+where
+    T: Depth,
+{
+    Ok(iter_increasing_measurements(measurements.iter().copied()).count())
+}
+
+pub fn solve_part2<T>(measurements: &[(usize, T)]) -> anyhow::Result<usize>
+where
+    T: Depth,
+{
+    Ok(iter_increasing_3_window_sums(measurements)?.count())
+}
+
+/// Like [`solve_part1`], but reads measurements of type `T` one line at a time from `reader`.
+pub fn solve_part1_from_reader<T>(reader: impl BufRead) -> Result<usize, ReadError>
+where
+    T: Depth,
+{
+    process_results(read_measurements::<T>(reader), |measurements| {
+        iter_increasing_measurements(measurements).count()
+    })
+}
+
+/// Like [`solve_part2`], but reads measurements of type `T` one line at a time from `reader`, and
+/// only holds a single window of them in memory at a time.
+pub fn solve_part2_from_reader<T>(reader: impl BufRead) -> anyhow::Result<usize>
+where
+    T: Depth,
+{
+    process_results(read_measurements::<T>(reader), |measurements| {
+        iter_increasing_windows(measurements, 3).map(Iterator::count)
+    })?
+}
+
+#[test]
+fn from_reader() {
+    assert_eq!(
+        solve_part1_from_reader::<u16>(EXAMPLE.as_bytes()).unwrap(),
+        7
+    );
+    assert_eq!(
+        solve_part1_from_reader::<u16>(INPUT.as_bytes()).unwrap(),
+        1288
+    );
+    assert_eq!(
+        solve_part2_from_reader::<u16>(EXAMPLE.as_bytes()).unwrap(),
+        5
+    );
+    assert_eq!(
+        solve_part2_from_reader::<u16>(INPUT.as_bytes()).unwrap(),
+        1311
+    );
+
+    match solve_part1_from_reader::<u16>("199\n\n  2OO\n".as_bytes()) {
+        Err(ReadError::Parse(ParseError { line, column, .. })) => {
+            assert_eq!((line, column), (3, 3))
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+
+    // Deeper and negative depths stream through just the same.
+    let sweep = "70000\n-5\n3000000000\n-1\n2999999999\n3000000001\n";
+    assert_eq!(solve_part1_from_reader::<i64>(sweep.as_bytes()).unwrap(), 3);
+    assert_eq!(solve_part2_from_reader::<i64>(sweep.as_bytes()).unwrap(), 2);
+    let sweep = "70000\n4000000000\n4000000001\n4000000002\n";
+    assert_eq!(solve_part1_from_reader::<u32>(sweep.as_bytes()).unwrap(), 3);
+    assert_eq!(solve_part2_from_reader::<u32>(sweep.as_bytes()).unwrap(), 1);
+    assert!(solve_part1_from_reader::<u16>(sweep.as_bytes()).is_err());
+    // So does the solver the CLI runs.
+    let parsed = Solver::parse(sweep).unwrap();
+    assert_eq!(
+        (
+            Solver::part1(&parsed).unwrap(),
+            Solver::part2(&parsed).unwrap()
+        ),
+        (3, 1)
+    );
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    const DAY: u8 = 1;
+
+    type Parsed = Vec<(usize, u32)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed> {
+        Ok(collect_measurements(input)?)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1> {
+        solve_part1(parsed)
+    }
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2> {
+        solve_part2(parsed)
+    }
+
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json> {
+        let increases = |value_key, increases: &mut dyn Iterator<Item = (usize, Json)>| {
+            let increases = increases.map(|(line_idx, value)| {
+                Json::object([("line_idx", line_idx.to_json()), (value_key, value)])
+            });
+            Json::object([("increases", Json::Array(increases.collect()))])
+        };
+        Ok(match part {
+            Part::One => increases(
+                "depth",
+                &mut iter_increasing_measurements(parsed.iter().copied())
+                    .map(|(line_idx, depth)| (line_idx, depth.to_json())),
+            ),
+            Part::Two => increases(
+                "window_sum",
+                &mut iter_increasing_3_window_sums(parsed)?
+                    .map(|(line_idx, sum)| (line_idx, sum.to_json())),
+            ),
+        })
+    }
 }
@@ -0,0 +1,232 @@
+//! Finding suspicious measurements in a sonar sweep, and leaving them out.
+
+use super::Depth;
+use crate::json::{Json, ToJson};
+use anyhow::ensure;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+};
+
+/// Settings for [`detect_anomalies`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AnomalySettings<S> {
+    /// How many measurements, centered on each one, to take the median of. Must be odd and at
+    /// least 3. Windows are cut short at the start and end of the sweep.
+    pub median_window: usize,
+    /// How far a measurement can be from its window's median before it's a spike.
+    pub max_deviation: S,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnomalyKind<T> {
+    /// The measurement is too far from the median of the measurements around it.
+    Spike { median: T },
+    /// The measurement is out of order in an otherwise strictly increasing or decreasing stretch
+    /// of two measurements on either side of it.
+    Glitch,
+}
+
+/// A suspicious measurement, on line `line_idx` of the sweep.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Anomaly<T> {
+    pub line_idx: usize,
+    pub depth: T,
+    pub kind: AnomalyKind<T>,
+}
+
+/// A human-readable description, with a 1-based line number.
+impl<T> Display for Anomaly<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} is ", self.line_idx + 1, self.depth)?;
+        match &self.kind {
+            AnomalyKind::Spike { median } => write!(f, "a spike (median {})", median),
+            AnomalyKind::Glitch => write!(f, "a glitch"),
+        }
+    }
+}
+
+impl<T> ToJson for Anomaly<T>
+where
+    T: ToJson,
+{
+    fn to_json(&self) -> Json {
+        let mut fields = vec![
+            ("line_idx", self.line_idx.to_json()),
+            ("depth", self.depth.to_json()),
+        ];
+        match &self.kind {
+            AnomalyKind::Spike { median } => {
+                fields.push(("kind", "spike".to_json()));
+                fields.push(("median", median.to_json()));
+            }
+            AnomalyKind::Glitch => fields.push(("kind", "glitch".to_json())),
+        }
+        Json::object(fields)
+    }
+}
+
+/// Finds the suspicious measurements in `measurements` (e.g., from
+/// [`super::collect_measurements`]), in order. A measurement that's both a spike and a glitch is
+/// reported as a spike.
+pub fn detect_anomalies<T>(
+    measurements: &[(usize, T)],
+    settings: AnomalySettings<T::Sum>,
+) -> anyhow::Result<Vec<Anomaly<T>>>
+where
+    T: Depth,
+{
+    ensure!(
+        settings.median_window >= 3 && settings.median_window % 2 == 1,
+        "median windows must contain an odd number of at least 3 measurements, not {}",
+        settings.median_window
+    );
+    let radius = settings.median_window / 2;
+    let depth = |idx: usize| measurements[idx].1;
+
+    let mut window = Vec::with_capacity(settings.median_window);
+    Ok((0..measurements.len())
+        .filter_map(|idx| {
+            let (line_idx, depth_at_idx) = measurements[idx];
+            let window_range =
+                idx.saturating_sub(radius)..(idx + radius + 1).min(measurements.len());
+            window.clear();
+            window.extend(window_range.map(depth));
+            window.sort_unstable();
+            let median = window[(window.len() - 1) / 2];
+
+            let kind = if depth_at_idx.abs_diff(median) > settings.max_deviation {
+                AnomalyKind::Spike { median }
+            } else if idx >= 2 && idx + 2 < measurements.len() {
+                let around = [
+                    depth(idx - 2),
+                    depth(idx - 1),
+                    depth(idx + 1),
+                    depth(idx + 2),
+                ];
+                let trend = around[1].cmp(&around[2]);
+                let is_monotonic = trend != Ordering::Equal
+                    && around.windows(2).all(|pair| pair[0].cmp(&pair[1]) == trend);
+                let is_in_order =
+                    around[1].cmp(&depth_at_idx) == trend && depth_at_idx.cmp(&around[2]) == trend;
+                if is_monotonic && !is_in_order {
+                    AnomalyKind::Glitch
+                } else {
+                    return None;
+                }
+            } else {
+                return None;
+            };
+            Some(Anomaly {
+                line_idx,
+                depth: depth_at_idx,
+                kind,
+            })
+        })
+        .collect())
+}
+
+/// `measurements` without the `anomalies` [`detect_anomalies`] found in them, keeping each
+/// measurement's original line index, for counting increases in (e.g., with
+/// [`super::iter_increasing_windows`]). The `anomalies` may be in any order, or filtered.
+pub fn without_anomalies<T>(
+    measurements: &[(usize, T)],
+    anomalies: &[Anomaly<T>],
+) -> Vec<(usize, T)>
+where
+    T: Depth,
+{
+    let anomalous: HashSet<usize> = anomalies.iter().map(|anomaly| anomaly.line_idx).collect();
+    measurements
+        .iter()
+        .copied()
+        .filter(|(line_idx, _depth)| !anomalous.contains(line_idx))
+        .collect()
+}
+
+#[test]
+fn anomalies() {
+    use super::{collect_measurements, iter_increasing_measurements, solve_part2, EXAMPLE};
+
+    let sweep = "100\n101\n102\n103\n900\n105\n106\n99\n108\n110\n111\n112\n";
+    let measurements = collect_measurements::<u16>(sweep).unwrap();
+    let settings = AnomalySettings {
+        median_window: 5,
+        max_deviation: 20,
+    };
+    let anomalies = detect_anomalies(&measurements, settings).unwrap();
+    assert_eq!(
+        anomalies,
+        [
+            Anomaly {
+                line_idx: 4,
+                depth: 900,
+                kind: AnomalyKind::Spike { median: 105 }
+            },
+            Anomaly {
+                line_idx: 7,
+                depth: 99,
+                kind: AnomalyKind::Glitch
+            },
+        ]
+    );
+    assert_eq!(
+        anomalies[0].to_string(),
+        "line 5: 900 is a spike (median 105)"
+    );
+    assert_eq!(
+        anomalies[1].to_json().to_string(),
+        r#"{"line_idx":7,"depth":99,"kind":"glitch"}"#
+    );
+
+    let cleaned = without_anomalies(&measurements, &anomalies);
+    assert_eq!(cleaned.len(), 10);
+    assert!(!cleaned
+        .iter()
+        .any(|&(line_idx, _)| line_idx == 4 || line_idx == 7));
+    assert_eq!(
+        iter_increasing_measurements(cleaned.iter().copied()).count(),
+        9
+    );
+    assert_eq!(solve_part2(&cleaned).unwrap(), 7);
+    let reversed: Vec<_> = anomalies.iter().rev().copied().collect();
+    assert_eq!(without_anomalies(&measurements, &reversed), cleaned);
+    let spikes_only: Vec<_> = anomalies
+        .iter()
+        .filter(|anomaly| matches!(anomaly.kind, AnomalyKind::Spike { .. }))
+        .copied()
+        .collect();
+    assert_eq!(without_anomalies(&measurements, &spikes_only).len(), 11);
+
+    // A tighter deviation also catches the glitch as a spike.
+    let strict = AnomalySettings {
+        median_window: 5,
+        max_deviation: 5,
+    };
+    assert_eq!(
+        detect_anomalies(&measurements, strict).unwrap()[1].kind,
+        AnomalyKind::Spike { median: 106 }
+    );
+    // In the puzzle's example, 269 overshoots the depths rising around it.
+    let example = collect_measurements::<u16>(EXAMPLE).unwrap();
+    assert_eq!(
+        detect_anomalies(&example, settings).unwrap(),
+        [Anomaly {
+            line_idx: 7,
+            depth: 269,
+            kind: AnomalyKind::Glitch
+        }]
+    );
+    assert!(detect_anomalies(
+        &example,
+        AnomalySettings {
+            median_window: 4,
+            ..settings
+        }
+    )
+    .is_err());
+}
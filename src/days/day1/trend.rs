@@ -0,0 +1,312 @@
+//! How the depths in a sonar sweep trend, for more than the puzzle's answer.
+
+use super::{iter_increasing_windows, Depth};
+use crate::json::{Json, ToJson};
+use anyhow::{anyhow, Context};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+};
+
+/// Consecutive measurements that each compare to the previous one in the same way.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Run {
+    pub first_line_idx: usize,
+    pub last_line_idx: usize,
+    /// The number of measurements in the run, which is at least 2.
+    pub measurements: usize,
+}
+
+impl ToJson for Run {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("first_line_idx", self.first_line_idx.to_json()),
+            ("last_line_idx", self.last_line_idx.to_json()),
+            ("measurements", self.measurements.to_json()),
+        ])
+    }
+}
+
+/// A change in depth between two consecutive measurements.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Jump<T> {
+    pub from_line_idx: usize,
+    pub to_line_idx: usize,
+    pub from: T,
+    pub to: T,
+}
+
+impl<T> Jump<T>
+where
+    T: Depth,
+{
+    pub fn size(&self) -> T::Sum {
+        self.from.abs_diff(self.to)
+    }
+}
+
+impl<T> ToJson for Jump<T>
+where
+    T: ToJson,
+{
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("from_line_idx", self.from_line_idx.to_json()),
+            ("to_line_idx", self.to_line_idx.to_json()),
+            ("from", self.from.to_json()),
+            ("to", self.to.to_json()),
+        ])
+    }
+}
+
+/// How the depths in a sonar sweep trend, beyond the number of increases the puzzle asks for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrendReport<T> {
+    pub measurements: usize,
+    /// The first of the longest runs of increasing depths, if the depth ever increases.
+    pub longest_increasing: Option<Run>,
+    /// The first of the longest runs of decreasing depths, if the depth ever decreases.
+    pub longest_decreasing: Option<Run>,
+    /// The first of the longest runs of unchanging depths, if the depth ever stays the same.
+    pub longest_flat: Option<Run>,
+    /// The number of runs of unchanging depths.
+    pub plateaus: usize,
+    /// The first of the largest changes in depth in either direction, if there are at least two
+    /// measurements.
+    pub largest_jump: Option<Jump<T>>,
+    /// For each of the requested window sizes, the number of windows whose sums increase (see
+    /// [`iter_increasing_windows`]).
+    pub increases: Vec<(usize, usize)>,
+}
+
+/// Reports how the depths of `measurements` (e.g., from [`super::collect_measurements`]) trend,
+/// counting the increases for windows of each of `window_sizes`.
+pub fn trend_report<T>(
+    measurements: &[(usize, T)],
+    window_sizes: &[usize],
+) -> anyhow::Result<TrendReport<T>>
+where
+    T: Depth,
+{
+    let increases = window_sizes
+        .iter()
+        .map(|&window_size| {
+            let increases = iter_increasing_windows(measurements.iter().copied(), window_size)
+                .with_context(|| anyhow!("invalid window size {}", window_size))?;
+            Ok((window_size, increases.count()))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    let mut report = TrendReport {
+        measurements: measurements.len(),
+        longest_increasing: None,
+        longest_decreasing: None,
+        longest_flat: None,
+        plateaus: 0,
+        largest_jump: None,
+        increases,
+    };
+    let mut finish_run = |(ordering, run): (Ordering, Run)| {
+        let longest = match ordering {
+            Ordering::Greater => &mut report.longest_increasing,
+            Ordering::Less => &mut report.longest_decreasing,
+            Ordering::Equal => {
+                report.plateaus += 1;
+                &mut report.longest_flat
+            }
+        };
+        if longest.is_none_or(|longest| run.measurements > longest.measurements) {
+            *longest = Some(run);
+        }
+    };
+
+    let mut current_run = None::<(Ordering, Run)>;
+    let mut largest_jump = None::<Jump<T>>;
+    measurements.windows(2).for_each(|pair| {
+        let [(from_line_idx, from), (to_line_idx, to)] = [pair[0], pair[1]];
+        let ordering = to.cmp(&from);
+        match &mut current_run {
+            Some((run_ordering, run)) if *run_ordering == ordering => {
+                run.last_line_idx = to_line_idx;
+                run.measurements += 1;
+            }
+            _ => {
+                let run = Run {
+                    first_line_idx: from_line_idx,
+                    last_line_idx: to_line_idx,
+                    measurements: 2,
+                };
+                if let Some(finished) = current_run.replace((ordering, run)) {
+                    finish_run(finished);
+                }
+            }
+        }
+
+        let jump = Jump {
+            from_line_idx,
+            to_line_idx,
+            from,
+            to,
+        };
+        if largest_jump.is_none_or(|largest| jump.size() > largest.size()) {
+            largest_jump = Some(jump);
+        }
+    });
+    if let Some(finished) = current_run {
+        finish_run(finished);
+    }
+    report.largest_jump = largest_jump;
+    Ok(report)
+}
+
+/// A human-readable summary, with 1-based line numbers.
+impl<T> Display for TrendReport<T>
+where
+    T: Depth,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let run = |f: &mut Formatter<'_>, name, run: Option<Run>| match run {
+            Some(run) => writeln!(
+                f,
+                "longest {} run: {} measurements, lines {}-{}",
+                name,
+                run.measurements,
+                run.first_line_idx + 1,
+                run.last_line_idx + 1
+            ),
+            None => writeln!(f, "longest {} run: none", name),
+        };
+
+        writeln!(f, "measurements: {}", self.measurements)?;
+        run(f, "increasing", self.longest_increasing)?;
+        run(f, "decreasing", self.longest_decreasing)?;
+        run(f, "flat", self.longest_flat)?;
+        writeln!(f, "plateaus: {}", self.plateaus)?;
+        match self.largest_jump {
+            Some(jump) => writeln!(
+                f,
+                "largest jump: {} -> {} ({}{}), lines {}-{}",
+                jump.from,
+                jump.to,
+                if jump.to < jump.from { '-' } else { '+' },
+                jump.size(),
+                jump.from_line_idx + 1,
+                jump.to_line_idx + 1
+            )?,
+            None => writeln!(f, "largest jump: none")?,
+        }
+        self.increases
+            .iter()
+            .try_for_each(|(window_size, increases)| {
+                writeln!(
+                    f,
+                    "increases with windows of {}: {}",
+                    window_size, increases
+                )
+            })
+    }
+}
+
+impl<T> ToJson for TrendReport<T>
+where
+    T: ToJson,
+{
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("measurements", self.measurements.to_json()),
+            ("longest_increasing", self.longest_increasing.to_json()),
+            ("longest_decreasing", self.longest_decreasing.to_json()),
+            ("longest_flat", self.longest_flat.to_json()),
+            ("plateaus", self.plateaus.to_json()),
+            ("largest_jump", self.largest_jump.to_json()),
+            (
+                "increases",
+                Json::array(self.increases.iter().map(|(window_size, increases)| {
+                    Json::object([
+                        ("window_size", window_size.to_json()),
+                        ("increases", increases.to_json()),
+                    ])
+                })),
+            ),
+        ])
+    }
+}
+
+#[test]
+fn trends() {
+    use super::{collect_measurements, EXAMPLE};
+
+    let report = trend_report(&collect_measurements::<u16>(EXAMPLE).unwrap(), &[1, 3]).unwrap();
+    assert_eq!(
+        report,
+        TrendReport {
+            measurements: 10,
+            longest_increasing: Some(Run {
+                first_line_idx: 0,
+                last_line_idx: 3,
+                measurements: 4
+            }),
+            longest_decreasing: Some(Run {
+                first_line_idx: 3,
+                last_line_idx: 4,
+                measurements: 2
+            }),
+            longest_flat: None,
+            plateaus: 0,
+            largest_jump: Some(Jump {
+                from_line_idx: 5,
+                to_line_idx: 6,
+                from: 207,
+                to: 240
+            }),
+            increases: vec![(1, 7), (3, 5)],
+        }
+    );
+    assert_eq!(
+        report.to_string(),
+        "\
+measurements: 10
+longest increasing run: 4 measurements, lines 1-4
+longest decreasing run: 2 measurements, lines 4-5
+longest flat run: none
+plateaus: 0
+largest jump: 207 -> 240 (+33), lines 6-7
+increases with windows of 1: 7
+increases with windows of 3: 5
+"
+    );
+
+    let sweep = collect_measurements::<i64>("5\n5\n5\n\n-2\n-2\n0\n").unwrap();
+    let report = trend_report(&sweep, &[]).unwrap();
+    assert_eq!(
+        (report.longest_flat, report.plateaus),
+        (
+            Some(Run {
+                first_line_idx: 0,
+                last_line_idx: 2,
+                measurements: 3
+            }),
+            2
+        )
+    );
+    assert_eq!(
+        report.to_json().to_string(),
+        r#"{"measurements":6,"longest_increasing":{"first_line_idx":5,"last_line_idx":6,"#
+            .to_owned()
+            + r#""measurements":2},"longest_decreasing":{"first_line_idx":2,"last_line_idx":4,"#
+            + r#""measurements":2},"longest_flat":{"first_line_idx":0,"last_line_idx":2,"#
+            + r#""measurements":3},"plateaus":2,"largest_jump":{"from_line_idx":2,"#
+            + r#""to_line_idx":4,"from":5,"to":-2},"increases":[]}"#
+    );
+
+    let report = trend_report(&collect_measurements::<u16>("7\n").unwrap(), &[1]).unwrap();
+    assert_eq!(
+        (
+            report.longest_increasing,
+            report.largest_jump,
+            report.increases
+        ),
+        (None, None, vec![(1, 0)])
+    );
+    assert!(trend_report(&sweep, &[0]).is_err());
+}
@@ -1,7 +1,19 @@
-use std::str::FromStr;
+use std::{
+    fmt::{self, Display, Formatter},
+    io::BufRead,
+    str::FromStr,
+};
 
-use anyhow::{anyhow, bail, Context};
+use crate::{
+    error::{ParseError, ReadError},
+    json::{Json, ToJson},
+    parse::{read_records, Source},
+    solution::{Part, Solution},
+};
+use anyhow::Context;
+use itertools::process_results;
 
+#[cfg(test)]
 const EXAMPLE: &str = "\
 forward 5
 down 5
@@ -11,10 +23,10 @@ down 8
 forward 2
 ";
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct Part1Submarine {
-    horizontal_pos: u32,
-    depth: u32,
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Part1Submarine {
+    horizontal_pos: u64,
+    depth: u64,
 }
 
 impl Part1Submarine {
@@ -25,25 +37,45 @@ impl Part1Submarine {
         }
     }
 
-    pub fn exec_cmd(&mut self, cmd: SubmarineCommand) {
+    pub fn horizontal_pos(&self) -> u64 {
+        self.horizontal_pos
+    }
+
+    pub fn depth(&self) -> u64 {
+        self.depth
+    }
+
+    pub fn exec_cmd(&mut self, cmd: SubmarineCommand) -> anyhow::Result<()> {
         let Self {
             horizontal_pos,
             depth,
         } = self;
         match cmd {
             SubmarineCommand::Forward(value) => {
-                *horizontal_pos = horizontal_pos.checked_add(value.into()).unwrap()
+                *horizontal_pos = horizontal_pos
+                    .checked_add(value.into())
+                    .context("horizontal position overflowed")?
+            }
+            SubmarineCommand::Up(value) => {
+                *depth = depth
+                    .checked_sub(value.into())
+                    .context("submarine went above the surface")?
+            }
+            SubmarineCommand::Down(value) => {
+                *depth = depth
+                    .checked_add(value.into())
+                    .context("depth overflowed")?
             }
-            SubmarineCommand::Up(value) => *depth = depth.checked_sub(value.into()).unwrap(),
-            SubmarineCommand::Down(value) => *depth = depth.checked_add(value.into()).unwrap(),
         }
+        Ok(())
     }
 }
 
 #[test]
 fn part1_example() {
     let mut submarine = Part1Submarine::new();
-    SubmarineCommand::iter_from_lines(EXAMPLE).for_each(|cmd| submarine.exec_cmd(cmd));
+    SubmarineCommand::iter_from_lines(EXAMPLE)
+        .for_each(|cmd| submarine.exec_cmd(cmd.unwrap()).unwrap());
     assert_eq!(
         submarine,
         Part1Submarine {
@@ -53,58 +85,133 @@ fn part1_example() {
     );
 }
 
-enum SubmarineCommand {
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SubmarineCommand {
     Forward(u8),
     Up(u8),
     Down(u8),
 }
 
 impl SubmarineCommand {
-    pub fn iter_from_lines(input: &str) -> impl Iterator<Item = SubmarineCommand> + '_ {
-        input
-            .lines()
-            .map(|l| l.trim())
-            .enumerate()
-            .filter(|(_idx, l)| !l.is_empty())
-            .map(|(idx, l)| (idx, l.parse()))
-            .map(|(idx, res)| {
-                res.with_context(|| anyhow!("failed to parse line {}", idx))
-                    .unwrap()
-            })
+    pub fn iter_from_lines(
+        input: &str,
+    ) -> impl Iterator<Item = Result<SubmarineCommand, ParseError>> + '_ {
+        Source::new(2, input)
+            .records()
+            .map(move |(_idx, l)| l.parse().map_err(|e: ParseError| e.within(input, l)))
+    }
+
+    /// Like [`Self::iter_from_lines`], but reads commands one line at a time from `reader`.
+    pub fn iter_from_reader(
+        reader: impl BufRead,
+    ) -> impl Iterator<Item = Result<SubmarineCommand, ReadError>> {
+        read_records(reader).map(|res| {
+            let (idx, line) = res?;
+            let l = line.trim();
+            l.parse()
+                .map_err(|e: ParseError| e.within(&line, l).on_line(idx).into())
+        })
     }
 }
 
 impl FromStr for SubmarineCommand {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (raw_discriminant, raw_value) = s
-            .split_once(' ')
-            .context("no space found to split discriminant and value")?;
-
-        let value = || {
-            raw_value
-                .parse()
-                .with_context(|| anyhow!("failed to parse {:?} as value", raw_value))
-        };
+        let source = Source::new(2, s);
+        let (raw_discriminant, raw_value) = source
+            .pair(s, " ", "a command like `forward 5`")
+            .map_err(|e| e.with_hint("missing a space between the command and its distance"))?;
+
+        let value = || source.parse(raw_value, "a distance from 0 to 255");
 
         let cmd = match raw_discriminant {
             "forward" => Self::Forward(value()?),
             "down" => Self::Down(value()?),
             "up" => Self::Up(value()?),
-            unrecognized => bail!("unrecognized discriminant {:?}", unrecognized),
+            unrecognized => {
+                return Err(source
+                    .error(unrecognized, "`forward`, `down` or `up`")
+                    .with_hint("expected `forward|down|up`"))
+            }
         };
 
         Ok(cmd)
     }
 }
 
+/// The command as it appears in puzzle inputs, like `forward 5`.
+impl Display for SubmarineCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Forward(value) => write!(f, "forward {}", value),
+            Self::Down(value) => write!(f, "down {}", value),
+            Self::Up(value) => write!(f, "up {}", value),
+        }
+    }
+}
+
+#[test]
+fn display_round_trips() {
+    use crate::generate::Rng;
+
+    let mut rng = Rng::new(2);
+    (0..1000).for_each(|_| {
+        let value = rng.below(256) as u8;
+        let cmd = match rng.below(3) {
+            0 => SubmarineCommand::Forward(value),
+            1 => SubmarineCommand::Down(value),
+            _ => SubmarineCommand::Up(value),
+        };
+        assert_eq!(cmd.to_string().parse::<SubmarineCommand>().unwrap(), cmd);
+    });
+    assert_eq!(SubmarineCommand::Up(255).to_string(), "up 255");
+}
+
+#[test]
+fn parse_errors() {
+    let errors = SubmarineCommand::iter_from_lines("forward 5\n  sideways 3\nup 256\ndown\n")
+        .filter_map(Result::err)
+        .map(
+            |ParseError {
+                 line,
+                 column,
+                 found,
+                 ..
+             }| (line, column, found),
+        )
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            (2, 3, "sideways".to_owned()),
+            (3, 4, "256".to_owned()),
+            (4, 1, "down".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn messy_input() {
+    use crate::input::{messy, normalize};
+
+    let messy = messy(EXAMPLE);
+    assert_eq!(
+        Solver::parse(&normalize(&messy)).unwrap(),
+        Solver::parse(EXAMPLE).unwrap()
+    );
+    assert_eq!(solve_part1_from_reader(messy.as_bytes()).unwrap(), 150);
+    assert_eq!(solve_part2_from_reader(messy.as_bytes()).unwrap(), 900);
+}
+
+#[cfg(test)]
 const INPUT: &str = include_str!("day2_input.txt");
 
 #[test]
 fn part1() {
     let mut submarine = Part1Submarine::new();
-    SubmarineCommand::iter_from_lines(INPUT).for_each(|cmd| submarine.exec_cmd(cmd));
+    SubmarineCommand::iter_from_lines(INPUT)
+        .for_each(|cmd| submarine.exec_cmd(cmd.unwrap()).unwrap());
     assert_eq!(
         submarine,
         Part1Submarine {
@@ -120,12 +227,36 @@ fn part1() {
     assert_eq!(horizontal_pos.checked_mul(depth).unwrap(), 2322630)
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct Part2Submarine {
-    aim: u32,
+pub fn solve_part1(commands: impl IntoIterator<Item = SubmarineCommand>) -> anyhow::Result<u64> {
+    let mut submarine = Part1Submarine::new();
+    commands
+        .into_iter()
+        .try_for_each(|cmd| submarine.exec_cmd(cmd))?;
+
+    let Part1Submarine {
+        horizontal_pos,
+        depth,
+    } = submarine;
+    horizontal_pos
+        .checked_mul(depth)
+        .context("final position product overflowed")
+}
+
+impl ToJson for Part1Submarine {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("horizontal_pos", self.horizontal_pos.to_json()),
+            ("depth", self.depth.to_json()),
+        ])
+    }
+}
 
-    horizontal_pos: u32,
-    depth: u32,
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Part2Submarine {
+    aim: u64,
+
+    horizontal_pos: u64,
+    depth: u64,
 }
 
 impl Part2Submarine {
@@ -137,7 +268,19 @@ impl Part2Submarine {
         }
     }
 
-    pub fn exec_cmd(&mut self, cmd: SubmarineCommand) {
+    pub fn aim(&self) -> u64 {
+        self.aim
+    }
+
+    pub fn horizontal_pos(&self) -> u64 {
+        self.horizontal_pos
+    }
+
+    pub fn depth(&self) -> u64 {
+        self.depth
+    }
+
+    pub fn exec_cmd(&mut self, cmd: SubmarineCommand) -> anyhow::Result<()> {
         let Self {
             aim,
             horizontal_pos,
@@ -145,21 +288,32 @@ impl Part2Submarine {
         } = self;
         match cmd {
             SubmarineCommand::Forward(value) => {
-                *horizontal_pos = horizontal_pos.checked_add(value.into()).unwrap();
-                *depth = depth
-                    .checked_add(aim.checked_mul(value.into()).unwrap())
-                    .unwrap();
+                *horizontal_pos = horizontal_pos
+                    .checked_add(value.into())
+                    .context("horizontal position overflowed")?;
+                *depth = aim
+                    .checked_mul(value.into())
+                    .and_then(|descent| depth.checked_add(descent))
+                    .context("depth overflowed")?;
+            }
+            SubmarineCommand::Up(value) => {
+                *aim = aim
+                    .checked_sub(value.into())
+                    .context("submarine aimed above the surface")?
+            }
+            SubmarineCommand::Down(value) => {
+                *aim = aim.checked_add(value.into()).context("aim overflowed")?
             }
-            SubmarineCommand::Up(value) => *aim = aim.checked_sub(value.into()).unwrap(),
-            SubmarineCommand::Down(value) => *aim = aim.checked_add(value.into()).unwrap(),
         }
+        Ok(())
     }
 }
 
 #[test]
 fn part2_example() {
     let mut submarine = Part2Submarine::new();
-    SubmarineCommand::iter_from_lines(EXAMPLE).for_each(|cmd| submarine.exec_cmd(cmd));
+    SubmarineCommand::iter_from_lines(EXAMPLE)
+        .for_each(|cmd| submarine.exec_cmd(cmd.unwrap()).unwrap());
 
     assert_eq!(
         submarine,
@@ -174,7 +328,8 @@ fn part2_example() {
 #[test]
 fn part2() {
     let mut submarine = Part2Submarine::new();
-    SubmarineCommand::iter_from_lines(INPUT).for_each(|cmd| submarine.exec_cmd(cmd));
+    SubmarineCommand::iter_from_lines(INPUT)
+        .for_each(|cmd| submarine.exec_cmd(cmd.unwrap()).unwrap());
 
     let Part2Submarine {
         aim: _,
@@ -183,3 +338,95 @@ fn part2() {
     } = submarine;
     assert_eq!(horizontal_pos.checked_mul(depth).unwrap(), 2105273490);
 }
+
+impl ToJson for Part2Submarine {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("aim", self.aim.to_json()),
+            ("horizontal_pos", self.horizontal_pos.to_json()),
+            ("depth", self.depth.to_json()),
+        ])
+    }
+}
+
+pub fn solve_part2(commands: impl IntoIterator<Item = SubmarineCommand>) -> anyhow::Result<u64> {
+    let mut submarine = Part2Submarine::new();
+    commands
+        .into_iter()
+        .try_for_each(|cmd| submarine.exec_cmd(cmd))?;
+
+    let Part2Submarine {
+        aim: _,
+        horizontal_pos,
+        depth,
+    } = submarine;
+    horizontal_pos
+        .checked_mul(depth)
+        .context("final position product overflowed")
+}
+
+pub fn solve_part1_from_reader(reader: impl BufRead) -> anyhow::Result<u64> {
+    process_results(SubmarineCommand::iter_from_reader(reader), |commands| {
+        solve_part1(commands)
+    })?
+}
+
+pub fn solve_part2_from_reader(reader: impl BufRead) -> anyhow::Result<u64> {
+    process_results(SubmarineCommand::iter_from_reader(reader), |commands| {
+        solve_part2(commands)
+    })?
+}
+
+#[test]
+fn from_reader() {
+    assert_eq!(solve_part1_from_reader(INPUT.as_bytes()).unwrap(), 2322630);
+    assert_eq!(
+        solve_part2_from_reader(INPUT.as_bytes()).unwrap(),
+        2105273490
+    );
+
+    let error = SubmarineCommand::iter_from_reader("forward 5\r\n  up x\r\n".as_bytes())
+        .find_map(Result::err)
+        .unwrap();
+    match error {
+        ReadError::Parse(ParseError { line, column, .. }) => assert_eq!((line, column), (2, 6)),
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    const DAY: u8 = 2;
+
+    type Parsed = Vec<SubmarineCommand>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed> {
+        Ok(SubmarineCommand::iter_from_lines(input).collect::<Result<_, _>>()?)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1> {
+        solve_part1(parsed.iter().copied())
+    }
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2> {
+        solve_part2(parsed.iter().copied())
+    }
+
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json> {
+        Ok(match part {
+            Part::One => {
+                let mut submarine = Part1Submarine::new();
+                parsed.iter().try_for_each(|&cmd| submarine.exec_cmd(cmd))?;
+                submarine.to_json()
+            }
+            Part::Two => {
+                let mut submarine = Part2Submarine::new();
+                parsed.iter().try_for_each(|&cmd| submarine.exec_cmd(cmd))?;
+                submarine.to_json()
+            }
+        })
+    }
+}
@@ -1,12 +1,21 @@
-use anyhow::Context;
+use crate::{
+    error::{ParseError, ReadError},
+    json::{Json, ToJson},
+    parse::{read_records, Source},
+    solution::{Part, Solution},
+    trace::{self, Event},
+};
+use anyhow::{anyhow, bail, Context};
+use itertools::process_results;
 use std::{
     cmp::Ordering,
     fmt::{self, Display, Formatter},
-    mem::size_of,
+    io::{BufRead, Seek},
     ops::{Not, Shl},
 };
 use thiserror::Error as ThisError;
 
+#[cfg(test)]
 const EXAMPLE: &str = "\
 00100
 11110
@@ -22,36 +31,89 @@ const EXAMPLE: &str = "\
 01010
 ";
 
-fn parse_diagnostic_report(input: &str) -> impl Clone + Iterator<Item = Sample> + '_ {
-    let lines = input.lines().map(|l| l.trim());
-    
-    let line_len = lines
-        .clone()
-        .next()
-        .context("y u no have line")
-        .unwrap()
-        .len();
-    Sample::check_width(line_len).unwrap();
-
-    lines
-        .inspect(move |l| assert_eq!(l.len(), line_len))
-        .map(move |l| {
-            let data = l.chars().fold(0, |acc, c| {
-                (acc << 1)
-                    | match c {
-                        '0' => 0,
-                        '1' => 1,
-                        c => panic!("blarg invalid character {:?}", c),
-                    }
-            });
-            Sample {
-                data,
-                width: line_len,
+/// Parses report lines one at a time, requiring every line to be as wide as the first.
+#[derive(Clone, Debug, Default)]
+pub struct ReportLineParser {
+    width: Option<usize>,
+}
+
+impl ReportLineParser {
+    pub fn new() -> Self {
+        Self { width: None }
+    }
+
+    fn expected_first_line() -> String {
+        format!(
+            "a line of at most {} binary digits",
+            Sample::max_width() - 1
+        )
+    }
+
+    /// Parses a single trimmed line, reporting errors relative to `l`.
+    pub fn parse_line(&mut self, l: &str) -> Result<Sample, ParseError> {
+        let source = Source::new(3, l);
+        let line_len = match self.width {
+            Some(width) => width,
+            None => {
+                if l.is_empty() || Sample::check_width(l.len()).is_err() {
+                    return Err(source.error(l, Self::expected_first_line()));
+                }
+                *self.width.insert(l.len())
             }
+        };
+
+        let data = l.char_indices().try_fold(0, |acc, (idx, c)| {
+            let bit = match c {
+                '0' => 0,
+                '1' => 1,
+                _ => {
+                    let found = &l[idx..idx + c.len_utf8()];
+                    return Err(source.error(found, "`0` or `1`"));
+                }
+            };
+            Ok((acc << 1) | bit)
+        })?;
+        if l.len() != line_len {
+            return Err(source.error(
+                l,
+                format!("{} binary digits, like the first line", line_len),
+            ));
+        }
+        Ok(Sample {
+            data,
+            width: line_len,
         })
+    }
 }
 
-struct SampleBitsStats {
+pub fn parse_diagnostic_report(input: &str) -> Result<Vec<Sample>, ParseError> {
+    let source = Source::new(3, input);
+    if source.records().next().is_none() {
+        return Err(source.error(source.end(), ReportLineParser::expected_first_line()));
+    }
+
+    let mut parser = ReportLineParser::new();
+    source
+        .records()
+        .map(|(_idx, l)| parser.parse_line(l).map_err(|e| e.within(input, l)))
+        .collect()
+}
+
+/// Like [`parse_diagnostic_report`], but reads samples one line at a time from `reader`.
+pub fn read_diagnostic_report(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<Sample, ReadError>> {
+    let mut parser = ReportLineParser::new();
+    read_records(reader).map(move |res| {
+        let (idx, line) = res?;
+        let l = line.trim();
+        parser
+            .parse_line(l)
+            .map_err(|e| e.within(&line, l).on_line(idx).into())
+    })
+}
+
+pub struct SampleBitsStats {
     counts_of_0s_and_1s: Vec<i32>,
 }
 
@@ -85,24 +147,24 @@ impl SampleBitsStats {
     }
 }
 
-fn most_common_bits_part1(bit_stats: &SampleBitsStats) -> Sample {
-    let most_common_bits =
-        bit_stats
-            .counts_of_0s_and_1s()
-            .iter()
-            .fold(0, |most_common_bits, &bucket| {
-                (most_common_bits << 1)
-                    | match bucket.cmp(&0) {
-                        Ordering::Equal => panic!("IDK what to do with this man"),
-                        Ordering::Greater => 1,
-                        Ordering::Less => 0,
-                    }
-            });
-
-    Sample::new(most_common_bits, bit_stats.sample_width())
+pub fn most_common_bits_part1(bit_stats: &SampleBitsStats) -> anyhow::Result<Sample> {
+    let most_common_bits = bit_stats
+        .counts_of_0s_and_1s()
+        .iter()
+        .enumerate()
+        .try_fold(0, |most_common_bits, (idx, &bucket)| {
+            Ok((most_common_bits << 1)
+                | match bucket.cmp(&0) {
+                    Ordering::Equal => bail!("IDK what to do with this man, bit {} is tied", idx),
+                    Ordering::Greater => 1,
+                    Ordering::Less => 0,
+                })
+        })?;
+
+    Ok(Sample::new(most_common_bits, bit_stats.sample_width()))
 }
 
-fn most_common_bits_part2(bit_stats: &SampleBitsStats) -> Sample {
+pub fn most_common_bits_part2(bit_stats: &SampleBitsStats) -> Sample {
     let most_common_bits =
         bit_stats
             .counts_of_0s_and_1s()
@@ -119,7 +181,7 @@ fn most_common_bits_part2(bit_stats: &SampleBitsStats) -> Sample {
     Sample::new(most_common_bits, bit_stats.sample_width())
 }
 
-fn least_common_bits_part2(bit_stats: &SampleBitsStats) -> Sample {
+pub fn least_common_bits_part2(bit_stats: &SampleBitsStats) -> Sample {
     let most_common_bits =
         bit_stats
             .counts_of_0s_and_1s()
@@ -136,20 +198,20 @@ fn least_common_bits_part2(bit_stats: &SampleBitsStats) -> Sample {
     Sample::new(most_common_bits, bit_stats.sample_width())
 }
 
-fn gamma(most_common_bits: Sample) -> Sample {
+pub fn gamma(most_common_bits: Sample) -> Sample {
     most_common_bits
 }
 
-fn epsilon(gamma: Sample) -> Sample {
+pub fn epsilon(gamma: Sample) -> Sample {
     !gamma
 }
 
-fn power_consumption(gamma: Sample, epsilon: Sample) -> u64 {
+pub fn power_consumption(gamma: Sample, epsilon: Sample) -> u64 {
     gamma.checked_mul(&epsilon).unwrap()
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct Sample {
+pub struct Sample {
     data: u32,
     width: usize,
 }
@@ -161,6 +223,16 @@ impl Display for Sample {
     }
 }
 
+/// The sample's bits as a string, along with their value.
+impl ToJson for Sample {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("bits", self.to_string().to_json()),
+            ("value", self.data.to_json()),
+        ])
+    }
+}
+
 impl Not for Sample {
     type Output = Self;
 
@@ -181,7 +253,7 @@ impl Shl<usize> for Sample {
 
 #[derive(Debug, ThisError)]
 #[error("{checked} is too wide for signal representation with max size of {max}")]
-struct SignalWidthError {
+pub struct SignalWidthError {
     checked: usize,
     max: usize,
 }
@@ -191,11 +263,11 @@ impl Sample {
         !(!0 << width)
     }
 
-    fn max_width() -> usize {
-        size_of::<u32>() * 8
+    pub fn max_width() -> usize {
+        u32::BITS as usize
     }
 
-    fn check_width(width: usize) -> Result<(), SignalWidthError> {
+    pub fn check_width(width: usize) -> Result<(), SignalWidthError> {
         if width < Self::max_width() {
             Ok(())
         } else {
@@ -240,54 +312,121 @@ impl Sample {
     pub fn checked_mul(&self, other: &Self) -> Option<u64> {
         let (data1, data2) = self.assert_compatible(other);
 
-        Some(u64::from(data1).checked_mul(u64::from(data2))?)
+        u64::from(data1).checked_mul(u64::from(data2))
+    }
+
+    /// Whether the first `prefix_len` bits of this sample match those of `prefix`.
+    fn matches_prefix(&self, prefix: u32, prefix_len: usize) -> bool {
+        let &Self { data, width } = self;
+        let prefix_mask = Self::mask(width) & !Self::mask(width - prefix_len);
+        (data ^ prefix) & prefix_mask == 0
     }
 
     pub fn is_bit_set(&self, idx: usize) -> bool {
         let &Self { data, width } = self;
         assert!(idx < width);
 
-        data & (1 << width - 1 - idx) != 0
+        data & (1 << (width - 1 - idx)) != 0
     }
 }
 
-#[cfg(test)]
-fn part1_gamma_and_epsilon(input: &str) -> (Sample, Sample) {
-    let report_samples_iter = parse_diagnostic_report(input);
-    let samples_bits_stats = SampleBitsStats::new(report_samples_iter).unwrap();
-    let most_common_bits = most_common_bits_part1(&samples_bits_stats);
+pub fn part1_gamma_and_epsilon(samples: &[Sample]) -> anyhow::Result<(Sample, Sample)> {
+    let samples_bits_stats =
+        SampleBitsStats::new(samples.iter().cloned()).context("no samples were provided")?;
+    gamma_and_epsilon(&samples_bits_stats)
+}
+
+fn gamma_and_epsilon(samples_bits_stats: &SampleBitsStats) -> anyhow::Result<(Sample, Sample)> {
+    let most_common_bits = most_common_bits_part1(samples_bits_stats)?;
     let gamma = gamma(most_common_bits);
     let epsilon = epsilon(gamma.clone());
-    (gamma, epsilon)
+    Ok((gamma, epsilon))
 }
 
 #[test]
 fn part1_example() {
-    let (gamma, epsilon) = part1_gamma_and_epsilon(EXAMPLE);
+    let samples = parse_diagnostic_report(EXAMPLE).unwrap();
+    let (gamma, epsilon) = part1_gamma_and_epsilon(&samples).unwrap();
     assert_eq!(gamma, Sample::new(0b10110, 5));
     assert_eq!(power_consumption(gamma, epsilon), 198);
 }
 
+#[test]
+fn parse_errors() {
+    let error = |input| {
+        let ParseError {
+            line,
+            column,
+            found,
+            ..
+        } = parse_diagnostic_report(input).unwrap_err();
+        (line, column, found)
+    };
+    assert_eq!(error(""), (1, 1, "".to_owned()));
+    assert_eq!(error("0101\n01x1\n"), (2, 3, "x".to_owned()));
+    assert_eq!(error("0101\n011\n"), (2, 1, "011".to_owned()));
+}
+
+#[test]
+fn messy_input() {
+    use crate::input::{messy, normalize};
+
+    let messy = messy(EXAMPLE);
+    assert_eq!(
+        Solver::parse(&normalize(&messy)).unwrap(),
+        Solver::parse(EXAMPLE).unwrap()
+    );
+    assert_eq!(solve_part1_from_reader(messy.as_bytes()).unwrap(), 198);
+    assert_eq!(
+        solve_part2_from_reader(std::io::Cursor::new(messy)).unwrap(),
+        230
+    );
+}
+
+#[cfg(test)]
 const INPUT: &str = include_str!("day3_input.txt");
 
 #[test]
 fn part1() {
-    let (gamma, epsilon) = part1_gamma_and_epsilon(INPUT);
+    let samples = parse_diagnostic_report(INPUT).unwrap();
+    let (gamma, epsilon) = part1_gamma_and_epsilon(&samples).unwrap();
     assert_eq!(power_consumption(gamma, epsilon), 2003336);
 }
 
-fn o2_generator_rating(report_samples_iter: impl Iterator<Item = Sample>) -> (usize, Sample) {
-    exactly_one_for_bit_criteria(report_samples_iter, most_common_bits_part2).unwrap()
+pub fn solve_part1(samples: &[Sample]) -> anyhow::Result<u64> {
+    let (gamma, epsilon) = part1_gamma_and_epsilon(samples)?;
+    Ok(power_consumption(gamma, epsilon))
+}
+
+pub fn solve_part1_from_reader(reader: impl BufRead) -> anyhow::Result<u64> {
+    let samples_bits_stats = process_results(read_diagnostic_report(reader), |samples| {
+        SampleBitsStats::new(samples)
+    })?
+    .context("no samples were provided")?;
+    let (gamma, epsilon) = gamma_and_epsilon(&samples_bits_stats)?;
+    Ok(power_consumption(gamma, epsilon))
+}
+
+/// A rating selected by bit criteria, paired with its index among the report's samples (which
+/// doesn't count blank lines, so it isn't necessarily its line index).
+pub type Rating = (usize, Sample);
+
+pub fn o2_generator_rating(
+    report_samples_iter: impl Iterator<Item = Sample>,
+) -> Result<Rating, BitCriteriaSelectionError> {
+    exactly_one_for_bit_criteria(report_samples_iter, most_common_bits_part2)
 }
 
-fn co2_scrubber_rating(report_samples_iter: impl Iterator<Item = Sample>) -> (usize, Sample) {
-    exactly_one_for_bit_criteria(report_samples_iter, least_common_bits_part2).unwrap()
+pub fn co2_scrubber_rating(
+    report_samples_iter: impl Iterator<Item = Sample>,
+) -> Result<Rating, BitCriteriaSelectionError> {
+    exactly_one_for_bit_criteria(report_samples_iter, least_common_bits_part2)
 }
 
-fn exactly_one_for_bit_criteria(
+pub fn exactly_one_for_bit_criteria(
     report_samples_iter: impl Iterator<Item = Sample>,
     mut next_bits_selection_gen: impl FnMut(&SampleBitsStats) -> Sample,
-) -> Result<(usize, Sample), BitCriteriaSelectionError> {
+) -> Result<Rating, BitCriteriaSelectionError> {
     let mut report_samples_iter = report_samples_iter.peekable();
 
     let sample_width = report_samples_iter
@@ -302,6 +441,11 @@ fn exactly_one_for_bit_criteria(
                 .unwrap();
         let this_bit_set = next_bits_selection_gen(&bit_stats_mask).is_bit_set(idx);
         report_samples.retain(|&(_idx, ref sample)| sample.is_bit_set(idx) == this_bit_set);
+        trace::emit(|| Event::Elimination {
+            bit_idx: idx,
+            kept: this_bit_set,
+            remaining: report_samples.len(),
+        });
 
         match report_samples.len() {
             0 => return Err(BitCriteriaSelectionError::AllCandidatesEliminated { after: idx }),
@@ -315,14 +459,14 @@ fn exactly_one_for_bit_criteria(
     })
 }
 
-fn life_support_rating(o2_generator_rating: Sample, co2_scrubber_rating: Sample) -> u64 {
+pub fn life_support_rating(o2_generator_rating: Sample, co2_scrubber_rating: Sample) -> u64 {
     o2_generator_rating
         .checked_mul(&co2_scrubber_rating)
         .unwrap()
 }
 
 #[derive(Debug, ThisError)]
-enum BitCriteriaSelectionError {
+pub enum BitCriteriaSelectionError {
     #[error("no samples were provided")]
     NoSamplesProvided,
     #[error("not enough candidates eliminated")]
@@ -334,17 +478,18 @@ enum BitCriteriaSelectionError {
     AllCandidatesEliminated { after: usize },
 }
 
-#[cfg(test)]
-fn part2_o2_and_co2_ratings(input: &str) -> ((usize, Sample), (usize, Sample)) {
-    let report_samples_iter = parse_diagnostic_report(input);
-    let o2_generator_rating = o2_generator_rating(report_samples_iter.clone());
-    let co2_scrubber_rating = co2_scrubber_rating(report_samples_iter);
-    (o2_generator_rating, co2_scrubber_rating)
+pub fn part2_o2_and_co2_ratings(
+    samples: &[Sample],
+) -> Result<(Rating, Rating), BitCriteriaSelectionError> {
+    let o2_generator_rating = o2_generator_rating(samples.iter().cloned())?;
+    let co2_scrubber_rating = co2_scrubber_rating(samples.iter().cloned())?;
+    Ok((o2_generator_rating, co2_scrubber_rating))
 }
 
 #[test]
 fn part2_example() {
-    let (o2_generator_rating, co2_scrubber_rating) = part2_o2_and_co2_ratings(EXAMPLE);
+    let samples = parse_diagnostic_report(EXAMPLE).unwrap();
+    let (o2_generator_rating, co2_scrubber_rating) = part2_o2_and_co2_ratings(&samples).unwrap();
     assert_eq!(o2_generator_rating, (3, Sample::new(0b10111, 5)));
     assert_eq!(co2_scrubber_rating, (11, Sample::new(0b01010, 5)));
 
@@ -360,10 +505,167 @@ fn part2_example() {
 #[test]
 fn part2() {
     let ((_, o2_generator_rating), (_, co2_scrubber_rating)) =
-        part2_o2_and_co2_ratings(INPUT);
+        part2_o2_and_co2_ratings(&parse_diagnostic_report(INPUT).unwrap()).unwrap();
 
     assert_eq!(
         life_support_rating(o2_generator_rating, co2_scrubber_rating),
         1877139
     );
 }
+
+pub fn solve_part2(samples: &[Sample]) -> anyhow::Result<u64> {
+    let ((_, o2_generator_rating), (_, co2_scrubber_rating)) = part2_o2_and_co2_ratings(samples)?;
+    Ok(life_support_rating(
+        o2_generator_rating,
+        co2_scrubber_rating,
+    ))
+}
+
+/// Like [`exactly_one_for_bit_criteria`], but re-reads `reader` once per bit instead of holding
+/// the remaining candidates in memory.
+pub fn exactly_one_for_bit_criteria_from_reader<R>(
+    reader: &mut R,
+    mut next_bits_selection_gen: impl FnMut(&SampleBitsStats) -> Sample,
+) -> anyhow::Result<Rating>
+where
+    R: BufRead + Seek,
+{
+    let mut prefix = 0;
+    let mut prefix_len = 0;
+    let mut kept = false;
+    loop {
+        reader.rewind().context("failed to rewind input")?;
+
+        let mut num_candidates = 0usize;
+        let mut last_candidate = None;
+        let candidates = read_diagnostic_report(&mut *reader)
+            .enumerate()
+            .map(|(idx, res)| res.map(|sample| (idx, sample)));
+        let bit_stats = process_results(candidates, |candidates| {
+            SampleBitsStats::new(
+                candidates
+                    .filter(|(_idx, sample)| sample.matches_prefix(prefix, prefix_len))
+                    .inspect(|candidate| {
+                        num_candidates += 1;
+                        last_candidate = Some(candidate.clone());
+                    })
+                    .map(|(_idx, sample)| sample),
+            )
+        })?;
+        if let Some(bit_idx) = prefix_len.checked_sub(1) {
+            trace::emit(|| Event::Elimination {
+                bit_idx,
+                kept,
+                remaining: num_candidates,
+            });
+        }
+
+        match (prefix_len, num_candidates, last_candidate) {
+            (0, 0, _) => return Err(BitCriteriaSelectionError::NoSamplesProvided.into()),
+            (_, 0, _) => {
+                return Err(BitCriteriaSelectionError::AllCandidatesEliminated {
+                    after: prefix_len - 1,
+                }
+                .into())
+            }
+            (1.., 1, Some(candidate)) => return Ok(candidate),
+            _ => (),
+        }
+        let bit_stats = bit_stats.context("no candidates, somehow")?;
+        if prefix_len == bit_stats.sample_width() {
+            bail!(
+                "not enough candidates eliminated, {} remaining",
+                num_candidates
+            );
+        }
+
+        let selected_bits = next_bits_selection_gen(&bit_stats);
+        kept = selected_bits.is_bit_set(prefix_len);
+        if kept {
+            prefix |= 1 << (selected_bits.width() - 1 - prefix_len);
+        }
+        prefix_len += 1;
+    }
+}
+
+/// Like [`solve_part2`], but makes a pass over `reader` per bit of each rating instead of holding
+/// the report in memory.
+pub fn solve_part2_from_reader(mut reader: impl BufRead + Seek) -> anyhow::Result<u64> {
+    let (_, o2_generator_rating) =
+        exactly_one_for_bit_criteria_from_reader(&mut reader, most_common_bits_part2)
+            .map_err(|e| anyhow!("failed to find O2 generator rating: {:#}", e))?;
+    let (_, co2_scrubber_rating) =
+        exactly_one_for_bit_criteria_from_reader(&mut reader, least_common_bits_part2)
+            .map_err(|e| anyhow!("failed to find CO2 scrubber rating: {:#}", e))?;
+    Ok(life_support_rating(
+        o2_generator_rating,
+        co2_scrubber_rating,
+    ))
+}
+
+#[test]
+fn from_reader() {
+    use std::io::Cursor;
+
+    assert_eq!(solve_part1_from_reader(EXAMPLE.as_bytes()).unwrap(), 198);
+    assert_eq!(solve_part1_from_reader(INPUT.as_bytes()).unwrap(), 2003336);
+
+    let mut reader = Cursor::new(EXAMPLE);
+    assert_eq!(
+        exactly_one_for_bit_criteria_from_reader(&mut reader, most_common_bits_part2).unwrap(),
+        (3, Sample::new(0b10111, 5)),
+    );
+    assert_eq!(
+        exactly_one_for_bit_criteria_from_reader(&mut reader, least_common_bits_part2).unwrap(),
+        (11, Sample::new(0b01010, 5)),
+    );
+    assert_eq!(solve_part2_from_reader(Cursor::new(EXAMPLE)).unwrap(), 230);
+    assert_eq!(
+        solve_part2_from_reader(Cursor::new(INPUT)).unwrap(),
+        1877139
+    );
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    const DAY: u8 = 3;
+
+    type Parsed = Vec<Sample>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed> {
+        Ok(parse_diagnostic_report(input)?)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1> {
+        solve_part1(parsed)
+    }
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2> {
+        solve_part2(parsed)
+    }
+
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json> {
+        Ok(match part {
+            Part::One => {
+                let (gamma, epsilon) = part1_gamma_and_epsilon(parsed)?;
+                Json::object([("gamma", gamma.to_json()), ("epsilon", epsilon.to_json())])
+            }
+            Part::Two => {
+                let rating = |(sample_idx, sample): Rating| {
+                    Json::object([
+                        ("sample_idx", sample_idx.to_json()),
+                        ("sample", sample.to_json()),
+                    ])
+                };
+                let (o2_generator_rating, co2_scrubber_rating) = part2_o2_and_co2_ratings(parsed)?;
+                Json::object([
+                    ("o2_generator_rating", rating(o2_generator_rating)),
+                    ("co2_scrubber_rating", rating(co2_scrubber_rating)),
+                ])
+            }
+        })
+    }
+}
@@ -1,8 +1,20 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
+use crate::{
+    error::ParseError,
+    grid::Grid,
+    json::{Json, ToJson},
+    parse::Source,
+    solution::{Part, Solution},
+    trace::{self, Event},
+};
 use anyhow::Context;
-use itertools::izip;
 
+#[cfg(test)]
 const EXAMPLE: &str = "\
 7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
 
@@ -25,52 +37,44 @@ const EXAMPLE: &str = "\
  2  0 12  3  7
 ";
 
+pub type Board = Grid<u8>;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct BingoGame {
+pub struct BingoGame {
     numbers_drawn: Vec<u8>,
-    player_boards: Vec<[[u8; 5]; 5]>,
+    player_boards: Vec<Board>,
+}
+
+impl BingoGame {
+    pub fn numbers_drawn(&self) -> &[u8] {
+        &self.numbers_drawn
+    }
+
+    pub fn player_boards(&self) -> &[Board] {
+        &self.player_boards
+    }
 }
 
 impl FromStr for BingoGame {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines().map(|s| s.trim());
-        let numbers_drawn = lines
-            .by_ref()
-            .take(1)
-            .next()
-            .context("no lines, you suck")?
-            .split(',')
-            .map(|raw_num| raw_num.parse::<u8>().unwrap())
-            .collect::<Vec<_>>();
-
-        let mut player_boards = Vec::new();
-
-        loop {
-            match lines.next() {
-                Some(l) if l.is_empty() => (),
-                Some(l) => panic!("WTF is this line doing yo: {:?}", l),
-                None => break,
-            }
-            let next_board: [[u8; 5]; 5] = lines
-                .by_ref()
-                .take(5)
-                .map(|l| {
-                    <[u8; 5]>::try_from(
-                        l.split_whitespace()
-                            .map(|t| t.parse().unwrap())
-                            .collect::<Vec<_>>(),
-                    )
-                    .unwrap()
-                })
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap();
+        let source = Source::new(4, s);
+        let mut sections = source.sections();
 
-            player_boards.push(next_board);
+        let mut first_section = sections.next().unwrap_or(source.end()).lines();
+        let numbers_drawn = first_section
+            .next()
+            .ok_or_else(|| source.error(source.end(), "a comma-separated list of drawn numbers"))?;
+        let numbers_drawn = source.list(numbers_drawn, ',', "a number from 0 to 255")?;
+        if let Some(l) = first_section.next() {
+            return Err(source.error(l.trim(), "a blank line before a board"));
         }
 
+        let player_boards = sections
+            .map(|section| source.fixed_grid(section, 5, 5, "board", "a number from 0 to 255"))
+            .collect::<Result<Vec<Board>, _>>()?;
+
         Ok(Self {
             numbers_drawn,
             player_boards,
@@ -78,6 +82,57 @@ impl FromStr for BingoGame {
     }
 }
 
+/// The game as it appears in puzzle inputs: the drawn numbers, then each board after a blank line,
+/// with its numbers right-aligned in columns.
+impl Display for BingoGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.numbers_drawn
+            .iter()
+            .enumerate()
+            .try_for_each(|(idx, n)| {
+                let separator = if idx == 0 { "" } else { "," };
+                write!(f, "{}{}", separator, n)
+            })?;
+        writeln!(f)?;
+
+        self.player_boards.iter().try_for_each(|board| {
+            writeln!(f)?;
+            board.rows().try_for_each(|row| {
+                row.iter().enumerate().try_for_each(|(x, n)| {
+                    let separator = if x == 0 { "" } else { " " };
+                    write!(f, "{}{:>2}", separator, n)
+                })?;
+                writeln!(f)
+            })
+        })
+    }
+}
+
+#[test]
+fn display_round_trips() {
+    use crate::generate::Rng;
+
+    let mut rng = Rng::new(4);
+    (0..100).for_each(|_| {
+        let (num_numbers_drawn, num_boards) = (rng.between(1, 30), rng.below(5));
+        let mut number = || rng.below(256) as u8;
+        let numbers_drawn = (0..num_numbers_drawn).map(|_| number()).collect();
+        let player_boards = (0..num_boards)
+            .map(|_| Board::from([(); 5].map(|()| [(); 5].map(|()| number()))))
+            .collect();
+        let bingo_game = BingoGame {
+            numbers_drawn,
+            player_boards,
+        };
+        assert_eq!(
+            bingo_game.to_string().parse::<BingoGame>().unwrap(),
+            bingo_game
+        );
+    });
+
+    assert_eq!(EXAMPLE.parse::<BingoGame>().unwrap().to_string(), EXAMPLE);
+}
+
 #[test]
 fn part1_example() {
     let bingo_game = BingoGame::from_str(EXAMPLE).unwrap();
@@ -89,27 +144,27 @@ fn part1_example() {
                 19, 3, 26, 1
             ],
             player_boards: vec![
-                [
+                Board::from([
                     [22, 13, 17, 11, 0],
                     [8, 2, 23, 4, 24],
                     [21, 9, 14, 16, 7],
                     [6, 10, 3, 18, 5],
                     [1, 12, 20, 15, 19],
-                ],
-                [
+                ]),
+                Board::from([
                     [3, 15, 0, 2, 22],
                     [9, 18, 13, 17, 5],
                     [19, 8, 7, 25, 23],
                     [20, 11, 10, 24, 4],
                     [14, 21, 16, 12, 6],
-                ],
-                [
+                ]),
+                Board::from([
                     [14, 21, 17, 24, 4],
                     [10, 16, 15, 9, 19],
                     [18, 8, 23, 26, 20],
                     [22, 11, 13, 6, 5],
                     [2, 0, 12, 3, 7],
-                ],
+                ]),
             ],
         }
     );
@@ -118,18 +173,58 @@ fn part1_example() {
         winners(&bingo_game).next(),
         Some(Winners {
             number_idx: 11,
-            winners: vec![((2, vec![("row", 0, [14, 21, 17, 24, 4,])]), 188)],
+            winners: vec![((2, vec![("row", 0, vec![14, 21, 17, 24, 4])]), 188)],
         }),
     );
 }
 
+/// A line on a board that completed it: `("row" | "column", line_idx, numbers)`.
+pub type WinningTrigger = (&'static str, usize, Vec<u8>);
+
 #[derive(Debug, Eq, PartialEq)]
-struct Winners {
-    number_idx: usize,
-    winners: Vec<((usize, Vec<(&'static str, usize, [u8; 5])>), u32)>,
+pub struct Winners {
+    pub number_idx: usize,
+    pub winners: Vec<((usize, Vec<WinningTrigger>), u32)>,
+}
+
+impl Winners {
+    /// The puzzle's score for the first of these winners: the number that was just drawn times the
+    /// sum of that board's unmarked numbers.
+    pub fn score(&self, bingo_game: &BingoGame) -> anyhow::Result<u32> {
+        let &(_, unmarked_sum) = self.winners.first().context("no winners, somehow")?;
+        u32::from(bingo_game.numbers_drawn[self.number_idx])
+            .checked_mul(unmarked_sum)
+            .context("score overflowed")
+    }
+}
+
+impl ToJson for Winners {
+    fn to_json(&self) -> Json {
+        let winners = self
+            .winners
+            .iter()
+            .map(|((player_idx, triggers), unmarked_sum)| {
+                let triggers = triggers.iter().map(|(line, line_idx, numbers)| {
+                    Json::object([
+                        ("line", line.to_json()),
+                        ("line_idx", line_idx.to_json()),
+                        ("numbers", numbers.to_json()),
+                    ])
+                });
+                Json::object([
+                    ("player_idx", player_idx.to_json()),
+                    ("triggers", Json::Array(triggers.collect())),
+                    ("unmarked_sum", unmarked_sum.to_json()),
+                ])
+            });
+        Json::object([
+            ("number_idx", self.number_idx.to_json()),
+            ("winners", Json::Array(winners.collect())),
+        ])
+    }
 }
 
-fn winners(bingo_game: &BingoGame) -> impl Iterator<Item = Winners> + '_ {
+pub fn winners(bingo_game: &BingoGame) -> impl Iterator<Item = Winners> + '_ {
     let BingoGame {
         numbers_drawn: next_numbers,
         player_boards,
@@ -138,10 +233,13 @@ fn winners(bingo_game: &BingoGame) -> impl Iterator<Item = Winners> + '_ {
     let mut numbers_drawn = HashSet::new();
     let mut previous_winners = HashSet::new();
     next_numbers
-        .into_iter()
+        .iter()
         .enumerate()
         .filter_map(move |(number_idx, number)| {
-            eprintln!("Drawing number {}", number);
+            trace::emit(|| Event::Draw {
+                number_idx,
+                number: *number,
+            });
             numbers_drawn.insert(number);
 
             let winners = player_boards
@@ -149,37 +247,41 @@ fn winners(bingo_game: &BingoGame) -> impl Iterator<Item = Winners> + '_ {
                 .enumerate()
                 .filter(|(player_idx, _board)| !previous_winners.contains(player_idx))
                 .filter_map(|(player_idx, board)| {
+                    let is_complete =
+                        |line: &[u8]| line.iter().all(|number| numbers_drawn.contains(number));
+
                     // check rows
-                    let winning_rows = board.iter().enumerate().filter(|(_idx, row)| {
-                        row.iter().all(|number| numbers_drawn.contains(number))
-                    });
+                    let winning_rows = board
+                        .rows()
+                        .map(<[u8]>::to_vec)
+                        .enumerate()
+                        .filter(|(_idx, row)| is_complete(row));
 
                     // check columns
-                    let [one, two, three, four, five] = &board;
-                    let winning_columns = izip!(one, two, three, four, five)
-                        .map(|(&one, &two, &three, &four, &five)| [one, two, three, four, five])
+                    let winning_columns = board
+                        .columns()
+                        .map(|col| col.copied().collect::<Vec<_>>())
                         .enumerate()
-                        .filter(|(_idx, col)| {
-                            col.iter().all(|number| numbers_drawn.contains(number))
-                        });
+                        .filter(|(_idx, col)| is_complete(col));
 
                     let winning_triggers = winning_rows
-                        .map(|(idx, row)| ("row", idx, row.clone()))
+                        .map(|(idx, row)| ("row", idx, row))
                         .chain(winning_columns.map(|(idx, col)| ("column", idx, col)))
                         .collect::<Vec<_>>();
 
                     (!winning_triggers.is_empty())
-                        .then(|| (player_idx, winning_triggers))
+                        .then_some((player_idx, winning_triggers))
                         .map(|stuff| {
-                            eprintln!("summing unmarked numbers for player {}", player_idx);
-                            (
-                                stuff,
-                                board
-                                    .iter()
-                                    .flat_map(|row| row.iter().copied())
-                                    .filter(|n| !numbers_drawn.contains(n))
-                                    .fold(0u32, |acc, n| acc + u32::from(dbg!(n))),
-                            )
+                            let unmarked_sum = board
+                                .iter()
+                                .map(|(_coord, &n)| n)
+                                .filter(|n| !numbers_drawn.contains(n))
+                                .fold(0u32, |acc, n| acc + u32::from(n));
+                            trace::emit(|| Event::BoardWon {
+                                player_idx,
+                                unmarked_sum,
+                            });
+                            (stuff, unmarked_sum)
                         })
                 })
                 .collect::<Vec<_>>();
@@ -188,16 +290,53 @@ fn winners(bingo_game: &BingoGame) -> impl Iterator<Item = Winners> + '_ {
                 winners.iter().for_each(|((player_idx, ..), ..)| {
                     previous_winners.insert(*player_idx);
                 });
-                return Some(Winners {
+                Some(Winners {
                     number_idx,
                     winners,
-                });
+                })
             } else {
                 None
             }
         })
 }
 
+#[test]
+fn parse_errors() {
+    let error = |input: &str| {
+        let ParseError {
+            line,
+            column,
+            found,
+            ..
+        } = input.parse::<BingoGame>().unwrap_err();
+        (line, column, found)
+    };
+    assert_eq!(error(""), (1, 1, "".to_owned()));
+    assert_eq!(error("1,2,x"), (1, 5, "x".to_owned()));
+    assert_eq!(error("1,2\n1 2 3 4 5"), (2, 1, "1 2 3 4 5".to_owned()));
+    assert_eq!(
+        error("1,2\n\n1 2 3 4 5\n1 2 3 4"),
+        (4, 1, "1 2 3 4".to_owned())
+    );
+    assert_eq!(
+        error("1,2\n\n1 2 3 4 5\n1 2 3 4 256"),
+        (4, 9, "256".to_owned())
+    );
+    assert_eq!(error("1,2\n\n1 2 3 4 5\n"), (4, 1, "".to_owned()));
+}
+
+#[test]
+fn messy_input() {
+    use crate::input::{messy, normalize};
+
+    let messy = messy(EXAMPLE);
+    assert_eq!(
+        Solver::parse(&normalize(&messy)).unwrap(),
+        Solver::parse(EXAMPLE).unwrap()
+    );
+}
+
+#[cfg(test)]
 const INPUT: &str = include_str!("./day4_input.txt");
 
 #[test]
@@ -208,7 +347,7 @@ fn part1() {
         winners,
         Winners {
             number_idx: 16,
-            winners: vec![((45, vec![("column", 2, [49, 0, 13, 69, 57])]), 919)],
+            winners: vec![((45, vec![("column", 2, vec![49, 0, 13, 69, 57])]), 919)],
         }
     );
 
@@ -220,6 +359,13 @@ fn part1() {
     );
 }
 
+pub fn solve_part1(bingo_game: &BingoGame) -> anyhow::Result<u32> {
+    winners(bingo_game)
+        .next()
+        .context("nobody ever won")?
+        .score(bingo_game)
+}
+
 #[test]
 fn part2_example() {
     let bingo_game = EXAMPLE.parse::<BingoGame>().unwrap();
@@ -228,7 +374,7 @@ fn part2_example() {
         winners,
         Winners {
             number_idx: 14,
-            winners: vec![((1, vec![("column", 2, [0, 13, 7, 10, 16])]), 148)],
+            winners: vec![((1, vec![("column", 2, vec![0, 13, 7, 10, 16])]), 148)],
         }
     );
 
@@ -248,7 +394,7 @@ fn part2() {
         winners,
         Winners {
             number_idx: 86,
-            winners: vec![((78, vec![("row", 1, [4, 96, 50, 9, 8])]), 321)],
+            winners: vec![((78, vec![("row", 1, vec![4, 96, 50, 9, 8])]), 321)],
         }
     );
 
@@ -259,3 +405,46 @@ fn part2() {
         2568,
     );
 }
+
+pub fn solve_part2(bingo_game: &BingoGame) -> anyhow::Result<u32> {
+    winners(bingo_game)
+        .last()
+        .context("nobody ever won")?
+        .score(bingo_game)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    const DAY: u8 = 4;
+
+    type Parsed = BingoGame;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed> {
+        Ok(input.parse()?)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1> {
+        solve_part1(parsed)
+    }
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2> {
+        solve_part2(parsed)
+    }
+
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json> {
+        let mut winners = winners(parsed);
+        let winners = match part {
+            Part::One => winners.next(),
+            Part::Two => winners.last(),
+        }
+        .context("nobody ever won")?;
+        Ok(Json::object([
+            ("number", parsed.numbers_drawn[winners.number_idx].to_json()),
+            ("score", winners.score(parsed)?.to_json()),
+            ("winners", winners.to_json()),
+        ]))
+    }
+}
@@ -1,10 +1,20 @@
-use anyhow::{anyhow, Context};
+use crate::{
+    error::{ParseError, ReadError},
+    grid::Grid,
+    json::{Json, ToJson},
+    parse::{read_records, Source},
+    solution::{Part, Solution},
+};
+use anyhow::{ensure, Context};
+use itertools::process_results;
 use std::{
     cmp::Ordering,
-    ops::{Index, IndexMut},
+    fmt::{self, Display, Formatter},
+    io::BufRead,
     str::FromStr,
 };
 
+#[cfg(test)]
 const EXAMPLE: &str = "\
 0,9 -> 5,9
 8,0 -> 0,8
@@ -19,170 +29,128 @@ const EXAMPLE: &str = "\
 ";
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct Coordinate(usize, usize);
+pub struct Coordinate(pub usize, pub usize);
 
 impl FromStr for Coordinate {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s.split_once(',').context("no comma found")?;
+        let source = Source::new(5, s);
+        let (x, y) = source
+            .pair(s, ",", "a coordinate like `x,y`")
+            .map_err(|e| e.with_hint("missing `,` between `x` and `y`"))?;
 
         Ok(Self(
-            x.parse().context("failed to parse `x`")?,
-            y.parse().context("failed to parse `y`")?,
+            source.parse(x, "an `x` coordinate")?,
+            source.parse(y, "a `y` coordinate")?,
         ))
     }
 }
 
+/// The coordinate as it appears in puzzle inputs, like `8,0`.
+impl Display for Coordinate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.0, self.1)
+    }
+}
+
+impl ToJson for Coordinate {
+    fn to_json(&self) -> Json {
+        Json::object([("x", self.0.to_json()), ("y", self.1.to_json())])
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct VentLine {
-    from: Coordinate,
-    to: Coordinate,
+pub struct VentLine {
+    pub from: Coordinate,
+    pub to: Coordinate,
 }
 
 impl FromStr for VentLine {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (from, to) = s
-            .split_once(" -> ")
-            .context("missing line operator ` -> `")?;
+        let (from, to) = Source::new(5, s)
+            .pair(s, " -> ", "a vent line like `x1,y1 -> x2,y2`")
+            .map_err(|e| e.with_hint("missing ` -> `"))?;
         Ok(Self {
-            from: from.parse().context("failed to parse `from` coordinate")?,
-            to: to.parse().context("failed to parse `to` coordinate")?,
+            from: from.parse().map_err(|e: ParseError| e.within(s, from))?,
+            to: to.parse().map_err(|e: ParseError| e.within(s, to))?,
         })
     }
 }
 
-fn parse_vent_lines(input: &str) -> impl Iterator<Item = VentLine> + '_ {
-    input.lines().map(|l| l.trim()).enumerate().map(|(idx, l)| {
-        l.parse()
-            .with_context(|| anyhow!("failed to parse line {} as a vent line", idx))
-            .unwrap()
-    })
-}
-
-struct Map<T> {
-    num_columns: usize,
-    num_rows: usize,
-    tiles: Vec<T>,
-}
-
-impl<T> Map<T> {
-    pub fn new(num_columns: usize, num_rows: usize, init: T) -> Self
-    where
-        T: Clone,
-    {
-        let tiles = vec![init.clone(); num_columns.checked_mul(num_rows).unwrap()];
-
-        Self {
-            num_columns,
-            num_rows,
-            tiles,
-        }
-    }
-
-    fn idx_to_coord(num_columns: usize, num_rows: usize, idx: usize) -> Coordinate {
-        assert!(idx < num_columns * num_rows);
-        Coordinate(idx % num_columns, idx / num_columns)
-    }
-
-    fn coord_to_idx(num_columns: usize, num_rows: usize, coord: Coordinate) -> usize {
-        let Coordinate(x, y) = coord;
-        assert!(num_columns > x);
-        assert!(num_rows > y);
-        y * num_columns + x // no need for overflow checking because we've already allocated this
-    }
-
-    pub fn into_iter(self) -> impl Iterator<Item = (Coordinate, T)> {
-        let Self {
-            num_rows,
-            num_columns,
-            tiles,
-        } = self;
-
-        tiles
-            .into_iter()
-            .enumerate()
-            .map(move |(idx, t)| (Self::idx_to_coord(num_columns, num_rows, idx), t))
+/// The vent line as it appears in puzzle inputs, like `8,0 -> 0,8`.
+impl Display for VentLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)
     }
 }
 
-impl<T> Index<Coordinate> for Map<T> {
-    type Output = T;
-
-    fn index(&self, index: Coordinate) -> &Self::Output {
-        let &Self {
-            num_columns,
-            num_rows,
-            ref tiles,
-        } = self;
-        &tiles[Self::coord_to_idx(num_columns, num_rows, index)]
-    }
+pub fn parse_vent_lines(input: &str) -> impl Iterator<Item = Result<VentLine, ParseError>> + '_ {
+    Source::new(5, input)
+        .records()
+        .map(move |(_idx, l)| l.parse().map_err(|e: ParseError| e.within(input, l)))
 }
 
-impl<T> IndexMut<Coordinate> for Map<T> {
-    fn index_mut(&mut self, index: Coordinate) -> &mut Self::Output {
-        let &mut Self {
-            num_columns,
-            num_rows,
-            ref mut tiles,
-        } = self;
-        &mut tiles[Self::coord_to_idx(num_columns, num_rows, index)]
-    }
+/// Like [`parse_vent_lines`], but reads vent lines one line at a time from `reader`.
+pub fn read_vent_lines(reader: impl BufRead) -> impl Iterator<Item = Result<VentLine, ReadError>> {
+    read_records(reader).map(|res| {
+        let (idx, line) = res?;
+        let l = line.trim();
+        l.parse()
+            .map_err(|e: ParseError| e.within(&line, l).on_line(idx).into())
+    })
 }
 
-fn map_danger_levels_part1<I>(vent_lines: I) -> impl Iterator<Item = (Coordinate, u32)>
+pub fn map_danger_levels_part1<I>(
+    vent_lines: I,
+) -> anyhow::Result<impl Iterator<Item = (Coordinate, u32)>>
 where
     I: IntoIterator<Item = VentLine>,
-    I::IntoIter: Clone,
 {
-    let vent_lines = vent_lines.into_iter();
-
-    let mut map = {
-        let (map_x, map_y) = vent_lines
-            .clone()
-            .fold((0, 0), |(x, y), VentLine { from, to }| {
-                (x.max(from.0).max(to.0), y.max(from.1).max(to.1))
-            });
-        Map::new(
-            map_x.checked_add(1).unwrap(),
-            map_y.checked_add(1).unwrap(),
-            0u32,
-        )
-    };
+    let mut map = Grid::new(0, 0, 0u32)?;
 
     // only work with orthogonal lines for now
-    vent_lines.for_each(|VentLine { from, to }| {
-        if from.0 == to.0 {
-            let y_iter = if from.1 > to.1 {
-                to.1..=from.1
-            } else {
-                from.1..=to.1
-            };
-            y_iter.for_each(|y| {
-                let tile = &mut map[Coordinate(from.0, y)];
-                *tile = tile.checked_add(1).unwrap();
-            })
-        } else if from.1 == to.1 {
-            let x_iter = if from.0 > to.0 {
-                to.0..=from.0
-            } else {
-                from.0..=to.0
-            };
-            x_iter.for_each(|x| {
-                let tile = &mut map[Coordinate(x, from.1)];
-                *tile = tile.checked_add(1).unwrap();
-            })
-        }
-    });
+    vent_lines
+        .into_iter()
+        .try_for_each(|VentLine { from, to }| {
+            map.grow_to_fit((from.0.max(to.0), from.1.max(to.1)), 0)?;
+            if from.0 == to.0 {
+                let y_iter = if from.1 > to.1 {
+                    to.1..=from.1
+                } else {
+                    from.1..=to.1
+                };
+                y_iter.for_each(|y| {
+                    let tile = &mut map[(from.0, y)];
+                    *tile = tile.checked_add(1).unwrap();
+                })
+            } else if from.1 == to.1 {
+                let x_iter = if from.0 > to.0 {
+                    to.0..=from.0
+                } else {
+                    from.0..=to.0
+                };
+                x_iter.for_each(|x| {
+                    let tile = &mut map[(x, from.1)];
+                    *tile = tile.checked_add(1).unwrap();
+                })
+            }
+            Ok::<_, anyhow::Error>(())
+        })?;
 
-    map.into_iter().filter(|&(ref _idx, t)| (t >= 2))
+    Ok(map
+        .into_iter()
+        .filter(|&(_coord, t)| t >= 2)
+        .map(|((x, y), t)| (Coordinate(x, y), t)))
 }
 
 #[test]
 fn part1_example() {
-    let vent_lines = parse_vent_lines(EXAMPLE).collect::<Vec<VentLine>>();
+    let vent_lines = parse_vent_lines(EXAMPLE)
+        .collect::<Result<Vec<VentLine>, _>>()
+        .unwrap();
     assert_eq!(
         vent_lines,
         [
@@ -230,7 +198,9 @@ fn part1_example() {
     );
 
     assert_eq!(
-        map_danger_levels_part1(vent_lines).collect::<Vec<_>>(),
+        map_danger_levels_part1(vent_lines)
+            .unwrap()
+            .collect::<Vec<_>>(),
         [
             (Coordinate(3, 4), 2),
             (Coordinate(7, 4), 2),
@@ -241,70 +211,169 @@ fn part1_example() {
     );
 }
 
+#[test]
+fn display_round_trips() {
+    use crate::generate::Rng;
+
+    let mut rng = Rng::new(5);
+    let mut coordinate = || {
+        let mut component = || match rng.below(4) {
+            0 => usize::MAX,
+            _ => rng.below(1000) as usize,
+        };
+        Coordinate(component(), component())
+    };
+    (0..1000).for_each(|_| {
+        let vent_line = VentLine {
+            from: coordinate(),
+            to: coordinate(),
+        };
+        assert_eq!(
+            vent_line.to_string().parse::<VentLine>().unwrap(),
+            vent_line
+        );
+        assert_eq!(
+            vent_line.from.to_string().parse::<Coordinate>().unwrap(),
+            vent_line.from
+        );
+    });
+
+    let vent_lines = parse_vent_lines(EXAMPLE)
+        .map(Result::unwrap)
+        .map(|vent_line| format!("{}\n", vent_line))
+        .collect::<String>();
+    assert_eq!(vent_lines, EXAMPLE);
+}
+
+#[test]
+fn parse_errors() {
+    let errors = parse_vent_lines("0,9 -> 5,9\n 8,0 - 0,8\n9,4 -> 3,x\n2,2 -> 21\n")
+        .filter_map(Result::err)
+        .map(
+            |ParseError {
+                 line,
+                 column,
+                 found,
+                 ..
+             }| (line, column, found),
+        )
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            (2, 2, "8,0 - 0,8".to_owned()),
+            (3, 10, "x".to_owned()),
+            (4, 8, "21".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn huge_maps_are_errors() {
+    let vent_lines = parse_vent_lines(&format!("0,0 -> 0,{}", usize::MAX))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(map_danger_levels_part1(vent_lines.clone()).is_err());
+    assert!(map_danger_levels_part2(vent_lines).is_err());
+}
+
+#[test]
+fn messy_input() {
+    use crate::input::{messy, normalize};
+
+    let messy = messy(EXAMPLE);
+    assert_eq!(
+        Solver::parse(&normalize(&messy)).unwrap(),
+        Solver::parse(EXAMPLE).unwrap()
+    );
+    assert_eq!(solve_part1_from_reader(messy.as_bytes()).unwrap(), 5);
+    assert_eq!(solve_part2_from_reader(messy.as_bytes()).unwrap(), 12);
+}
+
+#[cfg(test)]
 const INPUT: &str = include_str!("./day5_input.txt");
 
 #[test]
 fn part1() {
     assert_eq!(
-        map_danger_levels_part1(parse_vent_lines(INPUT).collect::<Vec<_>>()).count(),
+        map_danger_levels_part1(
+            parse_vent_lines(INPUT)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        )
+        .unwrap()
+        .count(),
         4728,
     );
 }
 
-fn map_danger_levels_part2<I>(vent_lines: I) -> impl Iterator<Item = (Coordinate, u32)>
+pub fn solve_part1(vent_lines: &[VentLine]) -> anyhow::Result<usize> {
+    Ok(map_danger_levels_part1(vent_lines.iter().cloned())?.count())
+}
+
+pub fn solve_part1_from_reader(reader: impl BufRead) -> anyhow::Result<usize> {
+    process_results(read_vent_lines(reader), |vent_lines| {
+        Ok(map_danger_levels_part1(vent_lines)?.count())
+    })?
+}
+
+pub fn map_danger_levels_part2<I>(
+    vent_lines: I,
+) -> anyhow::Result<impl Iterator<Item = (Coordinate, u32)>>
 where
     I: IntoIterator<Item = VentLine>,
-    I::IntoIter: Clone,
 {
-    let vent_lines = vent_lines.into_iter();
-
-    let mut map = {
-        let (map_x, map_y) = vent_lines
-            .clone()
-            .fold((0, 0), |(x, y), VentLine { from, to }| {
-                (x.max(from.0).max(to.0), y.max(from.1).max(to.1))
-            });
-        Map::new(
-            map_x.checked_add(1).unwrap(),
-            map_y.checked_add(1).unwrap(),
-            0u32,
-        )
-    };
+    let mut map = Grid::new(0, 0, 0u32)?;
 
     // only work with orthogonal lines for now
-    vent_lines.for_each(|VentLine { from, to }| {
-        let abs_diff_and_increment = |x: usize, y| -> (usize, isize) {
-            match x.cmp(&y) {
-                Ordering::Equal => (0, 0),
-                Ordering::Greater => (x.wrapping_sub(y), -1),
-                Ordering::Less => (y.wrapping_sub(x), 1),
-            }
-        };
-        let (abs_diff_x, inc_x) = abs_diff_and_increment(from.0, to.0);
-        let (abs_diff_y, inc_y) = abs_diff_and_increment(from.1, to.1);
-        assert!(
-            abs_diff_x == 0 || abs_diff_y == 0 || (abs_diff_x == abs_diff_y),
-            "non-line found",
-        );
-        let num_tiles = isize::try_from(abs_diff_x.max(abs_diff_y))
-            .expect("diff is greater than range of `isize`");
-
-        (0..=num_tiles).for_each(|idx| {
-            let coord = Coordinate(
-                ((from.0 as isize) + (idx * inc_x)) as usize,
-                ((from.1 as isize) + (idx * inc_y)) as usize,
+    vent_lines
+        .into_iter()
+        .try_for_each(|VentLine { from, to }| {
+            map.grow_to_fit((from.0.max(to.0), from.1.max(to.1)), 0)?;
+            let abs_diff_and_increment = |x: usize, y| -> (usize, isize) {
+                match x.cmp(&y) {
+                    Ordering::Equal => (0, 0),
+                    Ordering::Greater => (x.wrapping_sub(y), -1),
+                    Ordering::Less => (y.wrapping_sub(x), 1),
+                }
+            };
+            let (abs_diff_x, inc_x) = abs_diff_and_increment(from.0, to.0);
+            let (abs_diff_y, inc_y) = abs_diff_and_increment(from.1, to.1);
+            ensure!(
+                abs_diff_x == 0 || abs_diff_y == 0 || (abs_diff_x == abs_diff_y),
+                "non-line found from {:?} to {:?}",
+                from,
+                to,
             );
-            map[coord] = map[coord.clone()].checked_add(1).unwrap();
-        });
-    });
+            let num_tiles = isize::try_from(abs_diff_x.max(abs_diff_y))
+                .context("diff is greater than range of `isize`")?;
+
+            (0..=num_tiles).for_each(|idx| {
+                let coord = (
+                    ((from.0 as isize) + (idx * inc_x)) as usize,
+                    ((from.1 as isize) + (idx * inc_y)) as usize,
+                );
+                map[coord] = map[coord].checked_add(1).unwrap();
+            });
+            Ok(())
+        })?;
 
-    map.into_iter().filter(|&(ref _idx, t)| (t >= 2))
+    Ok(map
+        .into_iter()
+        .filter(|&(_coord, t)| t >= 2)
+        .map(|((x, y), t)| (Coordinate(x, y), t)))
 }
 
 #[test]
 fn part2_example() {
     assert_eq!(
-        map_danger_levels_part2(parse_vent_lines(EXAMPLE).collect::<Vec<_>>()).collect::<Vec<_>>(),
+        map_danger_levels_part2(
+            parse_vent_lines(EXAMPLE)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        )
+        .unwrap()
+        .collect::<Vec<_>>(),
         [
             (Coordinate(7, 1), 2),
             (Coordinate(2, 2), 2),
@@ -325,7 +394,79 @@ fn part2_example() {
 #[test]
 fn part2() {
     assert_eq!(
-        map_danger_levels_part2(parse_vent_lines(INPUT).collect::<Vec<_>>()).count(),
+        map_danger_levels_part2(
+            parse_vent_lines(INPUT)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        )
+        .unwrap()
+        .count(),
         17717,
     );
 }
+
+pub fn solve_part2(vent_lines: &[VentLine]) -> anyhow::Result<usize> {
+    Ok(map_danger_levels_part2(vent_lines.iter().cloned())?.count())
+}
+
+pub fn solve_part2_from_reader(reader: impl BufRead) -> anyhow::Result<usize> {
+    process_results(read_vent_lines(reader), |vent_lines| {
+        Ok(map_danger_levels_part2(vent_lines)?.count())
+    })?
+}
+
+#[test]
+fn from_reader() {
+    assert_eq!(solve_part1_from_reader(EXAMPLE.as_bytes()).unwrap(), 5);
+    assert_eq!(solve_part1_from_reader(INPUT.as_bytes()).unwrap(), 4728);
+    assert_eq!(solve_part2_from_reader(EXAMPLE.as_bytes()).unwrap(), 12);
+    assert_eq!(solve_part2_from_reader(INPUT.as_bytes()).unwrap(), 17717);
+
+    let error = read_vent_lines("0,9 -> 5,9\n8,0 -> 0,x\n".as_bytes())
+        .find_map(Result::err)
+        .unwrap();
+    match error {
+        ReadError::Parse(ParseError { line, column, .. }) => assert_eq!((line, column), (2, 10)),
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    const DAY: u8 = 5;
+
+    type Parsed = Vec<VentLine>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed> {
+        Ok(parse_vent_lines(input).collect::<Result<_, _>>()?)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1> {
+        solve_part1(parsed)
+    }
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2> {
+        solve_part2(parsed)
+    }
+
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json> {
+        let vent_lines = parsed.iter().cloned();
+        let dangerous_points: Vec<_> = match part {
+            Part::One => map_danger_levels_part1(vent_lines)?.collect(),
+            Part::Two => map_danger_levels_part2(vent_lines)?.collect(),
+        };
+        let dangerous_points = dangerous_points.into_iter().map(|(coord, danger_level)| {
+            Json::object([
+                ("coordinate", coord.to_json()),
+                ("danger_level", danger_level.to_json()),
+            ])
+        });
+        Ok(Json::object([(
+            "dangerous_points",
+            Json::Array(dangerous_points.collect()),
+        )]))
+    }
+}
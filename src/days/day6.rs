@@ -1,3 +1,13 @@
+use crate::{
+    error::ParseError,
+    json::{Json, ToJson},
+    parse::Source,
+    solution::{Part, Solution},
+    trace::{self, Event},
+};
+use anyhow::{anyhow, bail, Context};
+
+#[cfg(test)]
 const EXAMPLE: &str = "3,4,3,1,2";
 
 #[derive(Clone, Debug)]
@@ -15,62 +25,154 @@ impl ReproductionTrain {
     }
 }
 
-fn run_simulation(input: &str, days: usize) -> usize {
+pub fn parse_initial_pool(input: &str) -> Result<Vec<u8>, ParseError> {
+    Source::new(6, input).list(input, ',', "a lanternfish timer")
+}
+
+pub fn run_simulation(initial_pool: &[u8], days: usize) -> anyhow::Result<usize> {
     const ADULT_REPRODUCTION_CYCLE_DAYS: usize = 7;
 
-    let initial_pool = input
-        .trim()
-        .split(',')
-        .map(|n| n.parse::<u8>().unwrap() as usize)
-        .collect::<Vec<_>>();
     let mut num_lanternfish = initial_pool.len();
 
     let mut reproduction_slots =
         [(); ADULT_REPRODUCTION_CYCLE_DAYS].map(|()| ReproductionTrain::empty());
     initial_pool
         .iter()
-        .cloned()
-        .for_each(|days_left_to_reproduce| match days_left_to_reproduce {
-            adult if adult < reproduction_slots.len() => reproduction_slots[adult].ready += 1,
-            still_a_minor => panic!("Yo, {} isn't an adult, get outta here.", still_a_minor),
-        });
+        .map(|&n| usize::from(n))
+        .try_for_each(|days_left_to_reproduce| match days_left_to_reproduce {
+            adult if adult < reproduction_slots.len() => {
+                reproduction_slots[adult].ready += 1;
+                Ok(())
+            }
+            still_a_minor => bail!("Yo, {} isn't an adult, get outta here.", still_a_minor),
+        })?;
 
-    (0..days).for_each(|day| {
+    (0..days).try_for_each(|day| {
         let today_slot_idx = day % reproduction_slots.len();
         let ReproductionTrain { ready, still_baby } = &mut reproduction_slots[today_slot_idx];
 
         let new_babies = *ready;
-        num_lanternfish = num_lanternfish.checked_add(new_babies).unwrap();
+        num_lanternfish = num_lanternfish
+            .checked_add(new_babies)
+            .with_context(|| anyhow!("lanternfish population overflowed on day {}", day))?;
 
-        *ready = ready.checked_add(*still_baby).unwrap();
+        *ready = ready
+            .checked_add(*still_baby)
+            .with_context(|| anyhow!("reproduction slot overflowed on day {}", day))?;
         *still_baby = 0;
 
         let baby_repro_slot = (today_slot_idx + 2) % reproduction_slots.len(); // simulate `day + ADULT_REPRODUCTION_CYCLE_DAYS + 2`
         let still_baby = &mut reproduction_slots[baby_repro_slot].still_baby;
-        *still_baby = still_baby.checked_add(new_babies).unwrap();
-    });
+        *still_baby = still_baby
+            .checked_add(new_babies)
+            .with_context(|| anyhow!("baby reproduction slot overflowed on day {}", day))?;
+        trace::emit(|| Event::SimulationStep {
+            days_simulated: day + 1,
+            population: num_lanternfish,
+        });
+        Ok::<_, anyhow::Error>(())
+    })?;
 
-    num_lanternfish
+    Ok(num_lanternfish)
 }
 
 #[test]
 fn part1_example() {
-    assert_eq!(run_simulation(EXAMPLE, 80), 5934);
+    assert_eq!(
+        run_simulation(&parse_initial_pool(EXAMPLE).unwrap(), 80).unwrap(),
+        5934
+    );
 }
 
+#[test]
+fn parse_errors() {
+    let ParseError {
+        line,
+        column,
+        found,
+        ..
+    } = parse_initial_pool("\n3,4,-3,1").unwrap_err();
+    assert_eq!((line, column, &*found), (2, 5, "-3"));
+
+    assert!(run_simulation(&parse_initial_pool("3,7").unwrap(), 1).is_err());
+}
+
+#[test]
+fn messy_input() {
+    use crate::input::{messy, normalize};
+
+    let messy = messy(EXAMPLE);
+    assert_eq!(
+        Solver::parse(&normalize(&messy)).unwrap(),
+        Solver::parse(EXAMPLE).unwrap()
+    );
+}
+
+#[cfg(test)]
 const INPUT: &str = include_str!("./day6_input.txt");
 
 #[test]
 fn part1() {
-    assert_eq!(run_simulation(INPUT, 80), 372984);
+    assert_eq!(
+        run_simulation(&parse_initial_pool(INPUT).unwrap(), 80).unwrap(),
+        372984
+    );
+}
+
+pub fn solve_part1(initial_pool: &[u8]) -> anyhow::Result<usize> {
+    run_simulation(initial_pool, 80)
 }
 
 #[test]
 fn part2_example() {
-    assert_eq!(run_simulation(EXAMPLE, 256), 26984457539);
+    assert_eq!(
+        run_simulation(&parse_initial_pool(EXAMPLE).unwrap(), 256).unwrap(),
+        26984457539
+    );
 }
 
 #[test]
 fn part2() {
-    assert_eq!(run_simulation(INPUT, 256), 1681503251694);
+    assert_eq!(
+        run_simulation(&parse_initial_pool(INPUT).unwrap(), 256).unwrap(),
+        1681503251694
+    );
+}
+
+pub fn solve_part2(initial_pool: &[u8]) -> anyhow::Result<usize> {
+    run_simulation(initial_pool, 256)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    const DAY: u8 = 6;
+
+    type Parsed = Vec<u8>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed> {
+        Ok(parse_initial_pool(input)?)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1> {
+        solve_part1(parsed)
+    }
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2> {
+        solve_part2(parsed)
+    }
+
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json> {
+        let days: usize = match part {
+            Part::One => 80,
+            Part::Two => 256,
+        };
+        Ok(Json::object([
+            ("initial_population", parsed.len().to_json()),
+            ("days", days.to_json()),
+            ("population", run_simulation(parsed, days)?.to_json()),
+        ]))
+    }
 }
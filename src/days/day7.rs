@@ -1,26 +1,42 @@
+use crate::{
+    error::ParseError,
+    json::{Json, ToJson},
+    parse::Source,
+    solution::{Part, Solution},
+    trace::{self, Event},
+};
+use anyhow::{anyhow, Context};
 use std::cmp::Ordering;
 
-fn parse_crab_sub_horiz_pos(input: &str) -> impl Iterator<Item = u32> + '_ {
-    input.trim().split(',').map(|l| l.parse().unwrap())
+pub fn parse_crab_sub_horiz_pos(input: &str) -> Result<Vec<u32>, ParseError> {
+    Source::new(7, input).list(input, ',', "a horizontal position")
 }
 
+#[cfg(test)]
 const EXAMPLE: &str = "16,1,2,0,4,2,7,1,2,14";
 
-fn cheapest_crab_sub_alignment(
-    input: &str,
+pub fn cheapest_crab_sub_alignment(
+    crab_sub_horiz_poses: &[u32],
     mut cost_fn: impl FnMut(u32, u32) -> u64,
-) -> (u64, Vec<u32>) {
-    let crab_sub_horiz_poses = parse_crab_sub_horiz_pos(input).collect::<Vec<_>>();
+) -> anyhow::Result<(u64, Vec<u32>)> {
     let mut cheapest_fuel_consumption = u64::MAX;
     let mut cheapest_indices = Vec::with_capacity(1);
-    (0..=crab_sub_horiz_poses.iter().copied().max().unwrap()).for_each(|pos| {
+    let max_pos = crab_sub_horiz_poses
+        .iter()
+        .copied()
+        .max()
+        .context("no crab subs to align")?;
+    (0..=max_pos).try_for_each(|pos| {
         let fuel_consumption = crab_sub_horiz_poses
             .iter()
             .copied()
             .map(|crab_pos| cost_fn(crab_pos, pos))
-            .fold(0u64, |acc, fuel_cost| {
-                acc.checked_add(fuel_cost.into()).unwrap()
-            });
+            .try_fold(0u64, |acc, fuel_cost| acc.checked_add(fuel_cost))
+            .with_context(|| anyhow!("fuel consumption for position {} overflowed", pos))?;
+        trace::emit(|| Event::Candidate {
+            position: pos,
+            fuel: fuel_consumption,
+        });
 
         match fuel_consumption.cmp(&cheapest_fuel_consumption) {
             Ordering::Less => {
@@ -33,12 +49,13 @@ fn cheapest_crab_sub_alignment(
             }
             Ordering::Greater => (),
         }
-    });
+        Ok::<_, anyhow::Error>(())
+    })?;
 
-    (cheapest_fuel_consumption, cheapest_indices)
+    Ok((cheapest_fuel_consumption, cheapest_indices))
 }
 
-fn abs_diff(x: u32, y: u32) -> u32 {
+pub fn abs_diff(x: u32, y: u32) -> u32 {
     if x < y {
         y.wrapping_sub(x)
     } else {
@@ -46,41 +63,121 @@ fn abs_diff(x: u32, y: u32) -> u32 {
     }
 }
 
-fn cheapest_crab_sub_alignment_part1(input: &str) -> (u64, Vec<u32>) {
-    cheapest_crab_sub_alignment(input, |x, y| abs_diff(x, y).into())
+pub fn cheapest_crab_sub_alignment_part1(
+    crab_sub_horiz_poses: &[u32],
+) -> anyhow::Result<(u64, Vec<u32>)> {
+    cheapest_crab_sub_alignment(crab_sub_horiz_poses, |x, y| abs_diff(x, y).into())
 }
 
 #[test]
 fn part1_example() {
-    let (cheapest_fuel_consumption, cheapest_indices) = cheapest_crab_sub_alignment_part1(EXAMPLE);
+    let crab_sub_horiz_poses = parse_crab_sub_horiz_pos(EXAMPLE).unwrap();
+    let (cheapest_fuel_consumption, cheapest_indices) =
+        cheapest_crab_sub_alignment_part1(&crab_sub_horiz_poses).unwrap();
     assert_eq!(cheapest_fuel_consumption, 37);
     assert_eq!(cheapest_indices, [2])
 }
 
+#[test]
+fn parse_errors() {
+    let ParseError {
+        line,
+        column,
+        found,
+        ..
+    } = parse_crab_sub_horiz_pos("16,1,,0").unwrap_err();
+    assert_eq!((line, column, &*found), (1, 6, ""));
+}
+
+#[test]
+fn messy_input() {
+    use crate::input::{messy, normalize};
+
+    let messy = messy(EXAMPLE);
+    assert_eq!(
+        Solver::parse(&normalize(&messy)).unwrap(),
+        Solver::parse(EXAMPLE).unwrap()
+    );
+}
+
+#[cfg(test)]
 const INPUT: &str = include_str!("./day7_input.txt");
 
 #[test]
 fn part1() {
-    let (cheapest_fuel_consumption, cheapest_indices) = cheapest_crab_sub_alignment_part1(INPUT);
+    let crab_sub_horiz_poses = parse_crab_sub_horiz_pos(INPUT).unwrap();
+    let (cheapest_fuel_consumption, cheapest_indices) =
+        cheapest_crab_sub_alignment_part1(&crab_sub_horiz_poses).unwrap();
     assert_eq!(cheapest_fuel_consumption, 364898);
     assert_eq!(cheapest_indices, [361])
 }
 
-fn cheapest_crab_sub_alignment_part2(input: &str) -> (u64, Vec<u32>) {
-    let triangular_sequence = |n: u32| n.checked_mul(n + 1).unwrap() / 2;
-    cheapest_crab_sub_alignment(input, |x, y| triangular_sequence(abs_diff(x, y)).into())
+pub fn solve_part1(crab_sub_horiz_poses: &[u32]) -> anyhow::Result<u64> {
+    let (cheapest_fuel_consumption, _) = cheapest_crab_sub_alignment_part1(crab_sub_horiz_poses)?;
+    Ok(cheapest_fuel_consumption)
+}
+
+pub fn cheapest_crab_sub_alignment_part2(
+    crab_sub_horiz_poses: &[u32],
+) -> anyhow::Result<(u64, Vec<u32>)> {
+    let triangular_sequence = |n: u64| n * (n + 1) / 2;
+    cheapest_crab_sub_alignment(crab_sub_horiz_poses, |x, y| {
+        triangular_sequence(abs_diff(x, y).into())
+    })
 }
 
 #[test]
 fn part2_example() {
-    let (cheapest_fuel_consumption, cheapest_indices) = cheapest_crab_sub_alignment_part2(EXAMPLE);
+    let crab_sub_horiz_poses = parse_crab_sub_horiz_pos(EXAMPLE).unwrap();
+    let (cheapest_fuel_consumption, cheapest_indices) =
+        cheapest_crab_sub_alignment_part2(&crab_sub_horiz_poses).unwrap();
     assert_eq!(cheapest_fuel_consumption, 168);
     assert_eq!(cheapest_indices, [5])
 }
 
 #[test]
 fn part2() {
-    let (cheapest_fuel_consumption, cheapest_indices) = cheapest_crab_sub_alignment_part2(INPUT);
+    let crab_sub_horiz_poses = parse_crab_sub_horiz_pos(INPUT).unwrap();
+    let (cheapest_fuel_consumption, cheapest_indices) =
+        cheapest_crab_sub_alignment_part2(&crab_sub_horiz_poses).unwrap();
     assert_eq!(cheapest_fuel_consumption, 104149091);
     assert_eq!(cheapest_indices, [500])
 }
+
+pub fn solve_part2(crab_sub_horiz_poses: &[u32]) -> anyhow::Result<u64> {
+    let (cheapest_fuel_consumption, _) = cheapest_crab_sub_alignment_part2(crab_sub_horiz_poses)?;
+    Ok(cheapest_fuel_consumption)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    const DAY: u8 = 7;
+
+    type Parsed = Vec<u32>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed> {
+        Ok(parse_crab_sub_horiz_pos(input)?)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1> {
+        solve_part1(parsed)
+    }
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2> {
+        solve_part2(parsed)
+    }
+
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json> {
+        let (fuel, positions) = match part {
+            Part::One => cheapest_crab_sub_alignment_part1(parsed)?,
+            Part::Two => cheapest_crab_sub_alignment_part2(parsed)?,
+        };
+        Ok(Json::object([
+            ("fuel", fuel.to_json()),
+            ("positions", positions.to_json()),
+        ]))
+    }
+}
@@ -0,0 +1,148 @@
+use crate::error::ParseError;
+use std::fmt::{self, Display, Formatter};
+
+/// Renders a [`ParseError`] against the input it came from, pointing at the offending text:
+///
+/// ```text
+/// error: day 5 input: expected a vent line like `x1,y1 -> x2,y2`
+///  --> day5_input.txt:2:1
+///   |
+/// 2 | 8,0 - 0,8
+///   | ^^^^^^^^^ missing ` -> `
+/// ```
+pub struct Diagnostic<'a> {
+    input: &'a str,
+    error: &'a ParseError,
+    source_name: Option<&'a str>,
+}
+
+impl<'a> Diagnostic<'a> {
+    pub fn new(input: &'a str, error: &'a ParseError) -> Self {
+        Self {
+            input,
+            error,
+            source_name: None,
+        }
+    }
+
+    /// Names the input (i.e., a file path) in the location line.
+    pub fn with_source_name(self, source_name: &'a str) -> Self {
+        Self {
+            source_name: Some(source_name),
+            ..self
+        }
+    }
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let &Self {
+            input,
+            error:
+                ParseError {
+                    day,
+                    line,
+                    column,
+                    ref found,
+                    ref expected,
+                    ref hint,
+                },
+            source_name,
+        } = self;
+
+        let source_line = input.lines().nth(line.saturating_sub(1)).unwrap_or("");
+        let line_number = line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        writeln!(f, "error: day {} input: expected {}", day, expected)?;
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter,
+            source_name.unwrap_or("<input>"),
+            line,
+            column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        if source_line.is_empty() {
+            writeln!(f, "{} |", line_number)?;
+        } else {
+            writeln!(f, "{} | {}", line_number, source_line)?;
+        }
+
+        // Keep tabs in the padding so that the carets line up with the line above.
+        let padding = source_line
+            .chars()
+            .take(column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let carets = "^".repeat(found.chars().count().max(1));
+        write!(f, "{} | {}{}", gutter, padding, carets)?;
+        match hint {
+            Some(hint) => writeln!(f, " {}", hint),
+            None if found.is_empty() => writeln!(f, " input ended here"),
+            None => writeln!(f, " found {:?}", found),
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "day5")]
+fn renders_vent_line_hint() {
+    use crate::days::day5::parse_vent_lines;
+
+    let input = "0,9 -> 5,9\n8,0 - 0,8\n";
+    let error = parse_vent_lines(input).find_map(Result::err).unwrap();
+    assert_eq!(
+        Diagnostic::new(input, &error)
+            .with_source_name("day5_input.txt")
+            .to_string(),
+        "\
+error: day 5 input: expected a vent line like `x1,y1 -> x2,y2`
+ --> day5_input.txt:2:1
+  |
+2 | 8,0 - 0,8
+  | ^^^^^^^^^ missing ` -> `
+"
+    );
+}
+
+#[test]
+#[cfg(feature = "day2")]
+fn renders_submarine_command_hint() {
+    use crate::days::day2::SubmarineCommand;
+
+    let input = "forward 5\n\tbackward 3\n";
+    let error = SubmarineCommand::iter_from_lines(input)
+        .find_map(Result::err)
+        .unwrap();
+    assert_eq!(
+        Diagnostic::new(input, &error).to_string(),
+        "\
+error: day 2 input: expected `forward`, `down` or `up`
+ --> <input>:2:2
+  |
+2 | \tbackward 3
+  | \t^^^^^^^^ expected `forward|down|up`
+"
+    );
+}
+
+#[test]
+#[cfg(feature = "day4")]
+fn renders_end_of_input() {
+    use crate::days::day4::BingoGame;
+
+    let input = "1,2\n\n1 2 3 4 5\n";
+    let error = input.parse::<BingoGame>().unwrap_err();
+    assert_eq!(
+        Diagnostic::new(input, &error).to_string(),
+        "\
+error: day 4 input: expected a board of 5 rows
+ --> <input>:4:1
+  |
+4 |
+  | ^ input ended here
+"
+    );
+}
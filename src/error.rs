@@ -0,0 +1,99 @@
+use std::{borrow::Cow, io};
+use thiserror::Error as ThisError;
+
+/// A malformed piece of puzzle input, located by its 1-based line and column (in `char`s).
+#[derive(Clone, Debug, Eq, PartialEq, ThisError)]
+#[error("day {day} input, line {line}, column {column}: expected {expected}, found {found:?}")]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub found: String,
+    pub expected: Cow<'static, str>,
+    /// A short suggestion for fixing the input, shown under `found` by
+    /// [`crate::diagnostics::Diagnostic`].
+    pub hint: Option<Cow<'static, str>>,
+}
+
+impl ParseError {
+    /// Points at `found`, which must be a subslice of `input`. Use an empty slice at the end of
+    /// `input` for input that ended too early.
+    pub fn new(day: u8, input: &str, found: &str, expected: impl Into<Cow<'static, str>>) -> Self {
+        let (line, column) = position(input, found);
+        Self {
+            day,
+            line,
+            column,
+            found: found.to_owned(),
+            expected: expected.into(),
+            hint: None,
+        }
+    }
+
+    /// Relocates an error from parsing a single line to line `line_idx` (0-based) of a larger
+    /// input.
+    pub fn on_line(self, line_idx: usize) -> Self {
+        Self {
+            line: line_idx + self.line,
+            ..self
+        }
+    }
+
+    pub fn with_hint(self, hint: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            hint: Some(hint.into()),
+            ..self
+        }
+    }
+
+    /// Relocates an error from parsing `sub`, a subslice of `input`, so that it points into
+    /// `input` instead.
+    pub fn within(self, input: &str, sub: &str) -> Self {
+        let (sub_line, sub_column) = position(input, sub);
+        let column = if self.line == 1 {
+            sub_column + self.column - 1
+        } else {
+            self.column
+        };
+        Self {
+            line: sub_line + self.line - 1,
+            column,
+            ..self
+        }
+    }
+}
+
+/// A failure while parsing puzzle input incrementally from a reader.
+#[derive(Debug, ThisError)]
+pub enum ReadError {
+    #[error("failed to read input")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// The 1-based line and column of the start of `sub` in `input`.
+fn position(input: &str, sub: &str) -> (usize, usize) {
+    let offset = (sub.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+    debug_assert!(offset <= input.len(), "{:?} is not part of the input", sub);
+    let before = input.get(..offset).unwrap_or(input);
+
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[test]
+fn positions() {
+    let input = "abc\ndéf\n";
+    let e = ParseError::new(0, input, &input[7..8], "nothing");
+    assert_eq!((e.line, e.column, &*e.found), (2, 3, "f"));
+
+    let e = ParseError::new(0, input, &input[input.len()..], "more");
+    assert_eq!((e.line, e.column, &*e.found), (3, 1, ""));
+
+    let line = &input[4..8];
+    let e = ParseError::new(0, line, &line[3..], "nothing").within(input, line);
+    assert_eq!((e.line, e.column), (2, 3));
+}
@@ -0,0 +1,259 @@
+//! Seedable generators of valid puzzle inputs of any size, for scale-testing and property-testing
+//! the solvers. The same seed and size always produce the same input.
+//!
+//! "Valid" means that every input parses and satisfies each puzzle's rules (e.g., the submarine
+//! never rises above the surface). Answers for very large inputs can still overflow a solver's
+//! arithmetic, which is reported as an error; each generator documents the sizes it's safe at.
+
+use std::fmt::Write;
+
+/// A small, fast pseudo-random number generator ([SplitMix64]), so that generating inputs doesn't
+/// need any dependencies.
+///
+/// [SplitMix64]: https://prng.di.unimi.it/splitmix64.c
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, which must not be empty. Slightly biased for huge bounds, which
+    /// doesn't matter for generating inputs.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "cannot pick a number below 0");
+        self.next_u64() % bound
+    }
+
+    /// A number in `low..=high`.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        (1..items.len()).rev().for_each(|idx| {
+            let other = self.below(idx as u64 + 1) as usize;
+            items.swap(idx, other);
+        });
+    }
+}
+
+/// Day 1: `count` sea floor depths, wandering up and down like the real sea floor.
+pub fn depths(rng: &mut Rng, count: usize) -> String {
+    // Small enough that sums of 3 depths fit in a `u16`.
+    const MAX_DEPTH: u64 = 20_000;
+
+    let mut depth = rng.between(100, 200);
+    (0..count).fold(String::new(), |mut input, _| {
+        writeln!(input, "{}", depth).unwrap();
+        depth = (depth + rng.between(0, 20))
+            .saturating_sub(8)
+            .min(MAX_DEPTH);
+        input
+    })
+}
+
+/// Day 2: `count` submarine commands. `up` never takes the aim (and so the part 1 depth) above
+/// the surface.
+///
+/// Every distance is at most 9, so the part 2 product is at most `729 * count.pow(3)`, which fits
+/// in day 2's `u64` answers for up to 200,000 commands.
+pub fn submarine_commands(rng: &mut Rng, count: usize) -> String {
+    let mut aim = 0;
+    (0..count).fold(String::new(), |mut input, _| {
+        let distance = rng.between(1, 9);
+        let command = match rng.below(3) {
+            0 if aim >= distance => {
+                aim -= distance;
+                "up"
+            }
+            0 | 1 => {
+                aim += distance;
+                "down"
+            }
+            _ => "forward",
+        };
+        writeln!(input, "{} {}", command, distance).unwrap();
+        input
+    })
+}
+
+/// Day 3: a diagnostic report of `count` distinct samples, rounded up to an odd number so that no
+/// bit is tied for part 1, and to at least 3.
+///
+/// Part 2's bit criteria eliminate every sample if all remaining candidates share a bit (even if
+/// there's only one of them), so samples are generated such that whenever several of them share a
+/// prefix, some continue it with a `0` and some with a `1`.
+pub fn diagnostic_report(rng: &mut Rng, count: usize) -> String {
+    let count = count.max(2) | 1;
+    let width = (usize::BITS - count.leading_zeros()).max(12) as usize;
+
+    fn split(rng: &mut Rng, prefix: u32, width: usize, count: usize, samples: &mut Vec<u32>) {
+        if count == 1 {
+            samples.push((prefix << width) | (rng.below(1 << width) as u32));
+            return;
+        }
+        let half_capacity = 1usize << (width - 1);
+        let zeros = rng.between(
+            count.saturating_sub(half_capacity).max(1) as u64,
+            (count - 1).min(half_capacity) as u64,
+        ) as usize;
+        split(rng, prefix << 1, width - 1, zeros, samples);
+        split(rng, (prefix << 1) | 1, width - 1, count - zeros, samples);
+    }
+
+    let mut samples = Vec::with_capacity(count);
+    split(rng, 0, width, count, &mut samples);
+    rng.shuffle(&mut samples);
+    samples.iter().fold(String::new(), |mut input, sample| {
+        writeln!(input, "{:0width$b}", sample, width = width).unwrap();
+        input
+    })
+}
+
+/// Day 4: a bingo game with `boards` boards, drawing every number so that every board wins.
+pub fn bingo_game(rng: &mut Rng, boards: usize) -> String {
+    let mut numbers = (0..100u8).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+    let mut input = numbers
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    input.push('\n');
+
+    (0..boards).for_each(|_| {
+        rng.shuffle(&mut numbers);
+        input.push('\n');
+        numbers[..25].chunks(5).for_each(|row| {
+            let row = row.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>();
+            writeln!(input, "{}", row.join(" ")).unwrap();
+        });
+    });
+    input
+}
+
+/// Day 5: `count` horizontal, vertical, and diagonal vent lines on a 1000 by 1000 map.
+pub fn vent_lines(rng: &mut Rng, count: usize) -> String {
+    const SIZE: u64 = 1000;
+
+    (0..count).fold(String::new(), |mut input, _| {
+        let (x1, y1) = (rng.below(SIZE), rng.below(SIZE));
+        let (x2, y2) = match rng.below(3) {
+            0 => (x1, (y1 + rng.between(1, SIZE - 1)) % SIZE),
+            1 => ((x1 + rng.between(1, SIZE - 1)) % SIZE, y1),
+            _ => {
+                // Head towards the middle of the map, so that there's always room to move.
+                let (dx, dy) = (x1 < SIZE / 2, y1 < SIZE / 2);
+                let room_x = if dx { SIZE - 1 - x1 } else { x1 };
+                let room_y = if dy { SIZE - 1 - y1 } else { y1 };
+                let len = rng.between(1, room_x.min(room_y));
+                let step = |start: u64, increasing| {
+                    if increasing {
+                        start + len
+                    } else {
+                        start - len
+                    }
+                };
+                (step(x1, dx), step(y1, dy))
+            }
+        };
+        writeln!(input, "{},{} -> {},{}", x1, y1, x2, y2).unwrap();
+        input
+    })
+}
+
+/// Day 6: `count` lanternfish timers, each from 1 to 5 like the real inputs.
+pub fn lanternfish(rng: &mut Rng, count: usize) -> String {
+    comma_separated((0..count).map(|_| rng.between(1, 5)))
+}
+
+/// Day 7: `count` crab submarine positions from 0 to 1999.
+pub fn crab_positions(rng: &mut Rng, count: usize) -> String {
+    comma_separated((0..count).map(|_| rng.below(2000)))
+}
+
+fn comma_separated(numbers: impl Iterator<Item = u64>) -> String {
+    let mut input = numbers.map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+    input.push('\n');
+    input
+}
+
+/// Generates an input for `day` from `seed`, where `size` is the number of lines, commands,
+/// samples, boards, vent lines, fish, or crabs. `None` if there's no generator for `day`.
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    let generator = match day {
+        1 => depths,
+        2 => submarine_commands,
+        3 => diagnostic_report,
+        4 => bingo_game,
+        5 => vent_lines,
+        6 => lanternfish,
+        7 => crab_positions,
+        _ => return None,
+    };
+    Some(generator(&mut Rng::new(seed), size))
+}
+
+#[test]
+fn generated_inputs_are_solvable() {
+    use crate::solution::{find, Part, SOLUTIONS};
+
+    SOLUTIONS.iter().for_each(|solution| {
+        [(0, 1), (1, 2), (2, 17), (3, 250)]
+            .into_iter()
+            .for_each(|(seed, size)| {
+                let input = generate(solution.day(), seed, size).unwrap();
+                let parsed = solution.parse(&input).unwrap_or_else(|e| {
+                    panic!(
+                        "day {} seed {} size {}: {:#}",
+                        solution.day(),
+                        seed,
+                        size,
+                        e
+                    )
+                });
+                Part::ALL.into_iter().for_each(|part| {
+                    if let Err(e) = solution.solve(&*parsed, part) {
+                        panic!(
+                            "day {} part {} seed {} size {}: {:#}",
+                            solution.day(),
+                            part,
+                            seed,
+                            size,
+                            e
+                        );
+                    }
+                });
+            });
+    });
+    // Day 2's largest safe size, where a random walk of the aim would overflow a `u32` answer.
+    if let Some(solution) = find(2) {
+        let parsed = solution.parse(&generate(2, 4, 200_000).unwrap()).unwrap();
+        Part::ALL.into_iter().for_each(|part| {
+            solution.solve(&*parsed, part).unwrap();
+        });
+    }
+    assert_eq!(generate(8, 0, 1), None);
+}
+
+#[test]
+fn generators_are_deterministic() {
+    (1..=7).for_each(|day| {
+        assert_eq!(generate(day, 42, 100), generate(day, 42, 100));
+        assert_ne!(generate(day, 42, 100), generate(day, 43, 100));
+    });
+    assert_eq!(generate(1, 7, 100).unwrap().lines().count(), 100);
+    assert_eq!(generate(3, 7, 100).unwrap().lines().count(), 101);
+}
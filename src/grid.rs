@@ -0,0 +1,445 @@
+//! A rectangular 2D grid of tiles, addressed by `(x, y)` with `(0, 0)` at the top left.
+
+use crate::error::ParseError;
+use anyhow::{anyhow, ensure, Context};
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{Index, IndexMut},
+};
+
+/// The offsets of the 4 orthogonal neighbours of a tile, clockwise from up.
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The offsets of the 8 orthogonal and diagonal neighbours of a tile, clockwise from up.
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Grid<T> {
+    num_columns: usize,
+    num_rows: usize,
+    tiles: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(num_columns: usize, num_rows: usize, init: T) -> anyhow::Result<Self>
+    where
+        T: Clone,
+    {
+        let num_tiles = num_columns.checked_mul(num_rows).with_context(|| {
+            anyhow!("{}x{} grid is too large to address", num_columns, num_rows)
+        })?;
+        let mut tiles = Vec::new();
+        tiles
+            .try_reserve_exact(num_tiles)
+            .with_context(|| anyhow!("failed to allocate {}x{} grid", num_columns, num_rows))?;
+        tiles.resize(num_tiles, init);
+
+        Ok(Self {
+            num_columns,
+            num_rows,
+            tiles,
+        })
+    }
+
+    /// Builds a grid from rows of tiles, which must all be the same length.
+    pub fn from_rows<R>(rows: impl IntoIterator<Item = R>) -> anyhow::Result<Self>
+    where
+        R: IntoIterator<Item = T>,
+    {
+        let mut num_columns = None;
+        let mut num_rows = 0;
+        let mut tiles = Vec::new();
+        rows.into_iter().try_for_each(|row| {
+            let len_before = tiles.len();
+            tiles.extend(row);
+            let row_len = tiles.len() - len_before;
+            let expected_len = *num_columns.get_or_insert(row_len);
+            ensure!(
+                row_len == expected_len,
+                "row {} has {} tiles, but previous rows have {}",
+                num_rows,
+                row_len,
+                expected_len,
+            );
+            num_rows += 1;
+            Ok(())
+        })?;
+
+        Ok(Self {
+            num_columns: num_columns.unwrap_or(0),
+            num_rows,
+            tiles,
+        })
+    }
+
+    /// Parses a grid with one tile per `char`, like:
+    ///
+    /// ```text
+    /// #..#
+    /// .##.
+    /// ```
+    ///
+    /// `parse_tile` returns `None` for characters that aren't tiles, which are reported as
+    /// `expected` in day `day`'s input. Every line must be the same width.
+    pub fn parse_chars(
+        day: u8,
+        input: &str,
+        expected: &'static str,
+        mut parse_tile: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut num_columns = None;
+        let mut num_rows = 0;
+        let mut tiles = Vec::new();
+        for l in input.lines() {
+            let mut row_len = 0;
+            for (byte_idx, c) in l.char_indices() {
+                let tile = parse_tile(c).ok_or_else(|| {
+                    ParseError::new(day, input, &l[byte_idx..byte_idx + c.len_utf8()], expected)
+                })?;
+                tiles.push(tile);
+                row_len += 1;
+            }
+            let expected_len = *num_columns.get_or_insert(row_len);
+            if row_len != expected_len {
+                return Err(ParseError::new(
+                    day,
+                    input,
+                    l,
+                    format!("a row of {} tiles", expected_len),
+                ));
+            }
+            num_rows += 1;
+        }
+
+        Ok(Self {
+            num_columns: num_columns.unwrap_or(0),
+            num_rows,
+            tiles,
+        })
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.num_columns
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn idx(&self, (x, y): (usize, usize)) -> Option<usize> {
+        // No need for overflow checking, because we've already allocated this.
+        (x < self.num_columns && y < self.num_rows).then(|| y * self.num_columns + x)
+    }
+
+    fn coord(num_columns: usize, idx: usize) -> (usize, usize) {
+        (idx % num_columns, idx / num_columns)
+    }
+
+    pub fn get(&self, coord: (usize, usize)) -> Option<&T> {
+        self.idx(coord).map(|idx| &self.tiles[idx])
+    }
+
+    pub fn get_mut(&mut self, coord: (usize, usize)) -> Option<&mut T> {
+        self.idx(coord).map(move |idx| &mut self.tiles[idx])
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.num_rows).then(|| &self.tiles[y * self.num_columns..][..self.num_columns])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.num_rows).map(|y| self.row(y).unwrap())
+    }
+
+    pub fn column(&self, x: usize) -> Option<impl Iterator<Item = &T> + '_> {
+        (x < self.num_columns).then(|| self.tiles.iter().skip(x).step_by(self.num_columns))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        (0..self.num_columns).map(|x| self.column(x).unwrap())
+    }
+
+    /// Every tile with its coordinate, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        let num_columns = self.num_columns;
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(move |(idx, t)| (Self::coord(num_columns, idx), t))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> + '_ {
+        let num_columns = self.num_columns;
+        self.tiles
+            .iter_mut()
+            .enumerate()
+            .map(move |(idx, t)| (Self::coord(num_columns, idx), t))
+    }
+
+    fn neighbours<'a>(
+        &self,
+        (x, y): (usize, usize),
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let (num_columns, num_rows) = (self.num_columns, self.num_rows);
+        offsets.iter().filter_map(move |&(dx, dy)| {
+            let x = x.checked_add_signed(dx).filter(|&x| x < num_columns)?;
+            let y = y.checked_add_signed(dy).filter(|&y| y < num_rows)?;
+            Some((x, y))
+        })
+    }
+
+    /// The coordinates of the tiles up, right, down, and left of `coord` that are in the grid.
+    pub fn neighbours_4(&self, coord: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.neighbours(coord, &NEIGHBOURS_4)
+    }
+
+    /// Like [`Self::neighbours_4`], but including diagonal neighbours.
+    pub fn neighbours_8(&self, coord: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.neighbours(coord, &NEIGHBOURS_8)
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            num_columns: self.num_columns,
+            num_rows: self.num_rows,
+            tiles: self.tiles.iter().map(&mut f).collect(),
+        }
+    }
+
+    /// Pairs up the tiles of two grids with the same dimensions.
+    pub fn zip<U>(self, other: Grid<U>) -> anyhow::Result<Grid<(T, U)>> {
+        ensure!(
+            (self.num_columns, self.num_rows) == (other.num_columns, other.num_rows),
+            "cannot zip a {}x{} grid with a {}x{} grid",
+            self.num_columns,
+            self.num_rows,
+            other.num_columns,
+            other.num_rows,
+        );
+        Ok(Grid {
+            num_columns: self.num_columns,
+            num_rows: self.num_rows,
+            tiles: self.tiles.into_iter().zip(other.tiles).collect(),
+        })
+    }
+
+    /// Resizes the grid to `num_columns` by `num_rows`, keeping the tiles that are still in
+    /// bounds where they were and filling new tiles with `init`.
+    pub fn resize(&mut self, num_columns: usize, num_rows: usize, init: T) -> anyhow::Result<()>
+    where
+        T: Clone,
+    {
+        if (num_columns, num_rows) == (self.num_columns, self.num_rows) {
+            return Ok(());
+        }
+
+        let mut resized = Self::new(num_columns, num_rows, init)?;
+        let old_num_columns = self.num_columns;
+        self.tiles.drain(..).enumerate().for_each(|(idx, t)| {
+            if let Some(tile) = resized.get_mut(Self::coord(old_num_columns, idx)) {
+                *tile = t;
+            }
+        });
+        *self = resized;
+        Ok(())
+    }
+
+    /// Grows the grid so that `(x, y)` is in bounds. Dimensions that need to grow are at least
+    /// doubled, so that growing one tile at a time stays cheap.
+    pub fn grow_to_fit(&mut self, (x, y): (usize, usize), init: T) -> anyhow::Result<()>
+    where
+        T: Clone,
+    {
+        let grown_len = |len: usize, idx: usize| -> anyhow::Result<usize> {
+            if idx < len {
+                return Ok(len);
+            }
+            let min_len = idx.checked_add(1).context("grid is too large to address")?;
+            Ok(min_len.max(len.saturating_mul(2)))
+        };
+        let num_columns = grown_len(self.num_columns, x)?;
+        let num_rows = grown_len(self.num_rows, y)?;
+        self.resize(num_columns, num_rows, init)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: (usize, usize)) -> &Self::Output {
+        let (num_columns, num_rows) = (self.num_columns, self.num_rows);
+        self.get(coord).unwrap_or_else(|| {
+            panic!(
+                "{:?} is out of bounds of a {}x{} grid",
+                coord, num_columns, num_rows
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, coord: (usize, usize)) -> &mut Self::Output {
+        let (num_columns, num_rows) = (self.num_columns, self.num_rows);
+        self.get_mut(coord).unwrap_or_else(|| {
+            panic!(
+                "{:?} is out of bounds of a {}x{} grid",
+                coord, num_columns, num_rows
+            )
+        })
+    }
+}
+
+impl<T, const C: usize, const R: usize> From<[[T; C]; R]> for Grid<T> {
+    fn from(rows: [[T; C]; R]) -> Self {
+        Self {
+            num_columns: C,
+            num_rows: R,
+            tiles: rows.into_iter().flatten().collect(),
+        }
+    }
+}
+
+/// Every tile with its coordinate, row by row.
+pub struct IntoIter<T> {
+    num_columns: usize,
+    tiles: std::iter::Enumerate<std::vec::IntoIter<T>>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = ((usize, usize), T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (idx, t) = self.tiles.next()?;
+        Some((Grid::<T>::coord(self.num_columns, idx), t))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tiles.size_hint()
+    }
+}
+
+impl<T> IntoIterator for Grid<T> {
+    type Item = ((usize, usize), T);
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            num_columns: self.num_columns,
+            tiles: self.tiles.into_iter().enumerate(),
+        }
+    }
+}
+
+/// Writes one row per line. Single-character tiles are written side by side, like the grids
+/// [`Grid::parse_chars`] reads; wider tiles are right-aligned in space-separated columns.
+impl<T> Display for Grid<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let tiles = self.tiles.iter().map(T::to_string).collect::<Vec<_>>();
+        let width = tiles.iter().map(|t| t.chars().count()).max().unwrap_or(0);
+        let separator = if width > 1 { " " } else { "" };
+        if self.num_columns == 0 {
+            return Ok(());
+        }
+        tiles.chunks(self.num_columns).try_for_each(|row| {
+            row.iter().enumerate().try_for_each(|(x, t)| {
+                let separator = if x == 0 { "" } else { separator };
+                write!(f, "{}{:>width$}", separator, t, width = width)
+            })?;
+            writeln!(f)
+        })
+    }
+}
+
+#[test]
+fn rows_columns_and_neighbours() {
+    let grid = Grid::from_rows([[1, 2, 3], [4, 5, 6]]).unwrap();
+    assert_eq!(grid, Grid::from([[1, 2, 3], [4, 5, 6]]));
+    assert_eq!((grid.num_columns(), grid.num_rows()), (3, 2));
+    assert_eq!(grid.get((2, 1)), Some(&6));
+    assert_eq!(grid.get((3, 0)), None);
+    assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 2, 3], [4, 5, 6]]);
+    assert_eq!(
+        grid.columns()
+            .map(|c| c.copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        [[1, 4], [2, 5], [3, 6]]
+    );
+    assert!(grid.column(3).is_none());
+    assert_eq!(
+        grid.neighbours_4((0, 0)).collect::<Vec<_>>(),
+        [(1, 0), (0, 1)]
+    );
+    assert_eq!(
+        grid.neighbours_8((1, 1)).collect::<Vec<_>>(),
+        [(1, 0), (2, 0), (2, 1), (0, 1), (0, 0)]
+    );
+
+    assert!(Grid::from_rows([vec![1, 2], vec![3]]).is_err());
+}
+
+#[test]
+fn transforms() {
+    let mut grid = Grid::new(2, 2, 0u32).unwrap();
+    grid[(1, 0)] = 7;
+    *grid.get_mut((0, 1)).unwrap() = 3;
+    let doubled = grid.map(|t| t * 2);
+    assert_eq!(doubled.rows().collect::<Vec<_>>(), [[0, 14], [6, 0]]);
+    let zipped = grid.clone().zip(doubled).unwrap();
+    assert_eq!(zipped[(1, 0)], (7, 14));
+    assert!(grid.clone().zip(Grid::new(1, 2, 0).unwrap()).is_err());
+
+    grid.grow_to_fit((2, 0), 1).unwrap();
+    assert_eq!(
+        grid.rows().collect::<Vec<_>>(),
+        [[0, 7, 1, 1], [3, 0, 1, 1]]
+    );
+    grid.resize(1, 3, 9).unwrap();
+    assert_eq!(
+        grid.into_iter().collect::<Vec<_>>(),
+        [((0, 0), 0), ((0, 1), 3), ((0, 2), 9),]
+    );
+    assert!(Grid::new(usize::MAX, 2, 0u8).is_err());
+}
+
+#[test]
+fn parse_and_display() {
+    let input = "#..\n.#.\n";
+    let grid = Grid::parse_chars(0, input, "`#` or `.`", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })
+    .unwrap();
+    assert_eq!(grid.get((1, 1)), Some(&true));
+    assert_eq!(
+        grid.map(|&wall| if wall { '#' } else { '.' }).to_string(),
+        input
+    );
+    assert_eq!(
+        Grid::from_rows([[22, 13, 0], [8, 2, 23]])
+            .unwrap()
+            .to_string(),
+        "22 13  0\n 8  2 23\n"
+    );
+
+    let error = Grid::parse_chars(0, "..\n.x\n", "`.`", |c| (c == '.').then_some(())).unwrap_err();
+    assert_eq!((error.line, error.column, &*error.found), (2, 2, "x"));
+    let error = Grid::parse_chars(0, "..\n.\n", "`.`", |c| (c == '.').then_some(())).unwrap_err();
+    assert_eq!(
+        (error.line, error.column, &*error.expected),
+        (2, 1, "a row of 2 tiles")
+    );
+}
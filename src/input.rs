@@ -0,0 +1,153 @@
+use crate::error::ReadError;
+use anyhow::{anyhow, Context};
+use std::{
+    borrow::Cow,
+    env, fs,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable naming a directory of puzzle inputs, for running against inputs from a
+/// different puzzle account than the bundled ones.
+pub const INPUT_DIR_ENV_VAR: &str = "AOC2021_INPUT_DIR";
+
+/// The directory of inputs bundled with this crate's source.
+pub const BUNDLED_INPUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/days");
+
+pub fn input_file_name(day: u8) -> String {
+    format!("day{}_input.txt", day)
+}
+
+/// Resolves puzzle inputs by day from a single directory, using [`input_file_name`] for each day.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InputProvider {
+    dir: PathBuf,
+}
+
+impl InputProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn bundled() -> Self {
+        Self::new(BUNDLED_INPUT_DIR)
+    }
+
+    /// Uses the directory in [`INPUT_DIR_ENV_VAR`] if it's set, falling back to
+    /// [`Self::bundled`].
+    pub fn from_env() -> Self {
+        env::var_os(INPUT_DIR_ENV_VAR)
+            .map(Self::new)
+            .unwrap_or_else(Self::bundled)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(input_file_name(day))
+    }
+
+    pub fn load(&self, day: u8) -> anyhow::Result<String> {
+        let path = self.path(day);
+        fs::read_to_string(&path).with_context(|| {
+            anyhow!(
+                "failed to read input for day {} from {}",
+                day,
+                path.display()
+            )
+        })
+    }
+}
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
+
+/// Irons out differences between editors and platforms that no parser should have to care about:
+/// strips a leading byte order mark, converts CRLF line breaks to LF, replaces tabs with spaces,
+/// and drops trailing blank lines (keeping the final line break, if any).
+///
+/// Every character left keeps its line and column, so parse errors still point at the right spot.
+/// Borrows `input` if it's already normalized.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let input = input.strip_prefix(BYTE_ORDER_MARK).unwrap_or(input);
+    let content = input.trim_end();
+    let line_break = if !content.is_empty() && input[content.len()..].contains('\n') {
+        "\n"
+    } else {
+        ""
+    };
+    let normalized_len = content.len() + line_break.len();
+    if !content.contains(['\r', '\t']) && input[content.len()..normalized_len] == *line_break {
+        return Cow::Borrowed(&input[..normalized_len]);
+    }
+    Cow::Owned(content.replace("\r\n", "\n").replace('\t', " ") + line_break)
+}
+
+/// Reads `reader` one line at a time, paired with each line's 0-based index, so that only a single
+/// line of input needs to be held in memory at once. Lines are normalized like [`normalize`].
+pub fn read_lines(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(usize, String), ReadError>> {
+    reader.lines().enumerate().map(|(idx, res)| {
+        let mut line = res?;
+        if idx == 0 && line.starts_with(BYTE_ORDER_MARK) {
+            line.remove(0);
+        }
+        if line.contains('\t') {
+            line = line.replace('\t', " ");
+        }
+        Ok((idx, line))
+    })
+}
+
+/// `input` as a careless editor might have saved it: with a byte order mark, CRLF line breaks,
+/// tabs instead of spaces, and trailing blank lines.
+#[cfg(test)]
+pub(crate) fn messy(input: &str) -> String {
+    let mut messy = String::from(BYTE_ORDER_MARK);
+    input.lines().for_each(|l| {
+        messy.push_str(&l.replace(' ', "\t"));
+        messy.push_str("\r\n");
+    });
+    messy.push_str("\r\n \t\n\n");
+    messy
+}
+
+#[test]
+fn normalizes() {
+    assert!(matches!(normalize("1\n2\n"), Cow::Borrowed("1\n2\n")));
+    assert!(matches!(normalize("1\n2\n\n \n"), Cow::Borrowed("1\n2\n")));
+    assert!(matches!(normalize("\u{feff}1 2"), Cow::Borrowed("1 2")));
+    assert_eq!(normalize(&messy("a b\n c\n")), "a b\n c\n");
+    assert_eq!(normalize("1\t2 \r\n"), "1 2\n");
+    assert_eq!(normalize("\n\n"), "");
+
+    let lines = read_lines(messy("a b\n c\n").as_bytes())
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(lines[..2], [(0, "a b".to_owned()), (1, " c".to_owned())]);
+}
+
+#[test]
+fn bundled_inputs_match_compiled_inputs() {
+    let provider = InputProvider::bundled();
+    assert_eq!(
+        provider.load(1).unwrap(),
+        include_str!("days/day1_input.txt")
+    );
+    assert_eq!(
+        provider.load(7).unwrap(),
+        include_str!("days/day7_input.txt")
+    );
+}
+
+#[test]
+fn missing_inputs_are_errors() {
+    let provider = InputProvider::new(env!("CARGO_MANIFEST_DIR"));
+    assert_eq!(
+        provider.path(3),
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("day3_input.txt")
+    );
+    assert!(provider.load(3).is_err());
+}
@@ -0,0 +1,181 @@
+//! Just enough JSON to report answers and the results behind them to other tools.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(i128),
+    String(String),
+    Array(Vec<Json>),
+    /// Fields are written in order, and aren't checked for duplicate keys.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<K>(fields: impl IntoIterator<Item = (K, Json)>) -> Self
+    where
+        K: Into<String>,
+    {
+        Self::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect(),
+        )
+    }
+
+    pub fn array<T>(items: impl IntoIterator<Item = T>) -> Self
+    where
+        T: ToJson,
+    {
+        Self::Array(items.into_iter().map(|item| item.to_json()).collect())
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    s.chars().try_for_each(|c| match c {
+        '"' => f.write_str("\\\""),
+        '\\' => f.write_str("\\\\"),
+        '\n' => f.write_str("\\n"),
+        '\r' => f.write_str("\\r"),
+        '\t' => f.write_str("\\t"),
+        c if c.is_control() => write!(f, "\\u{:04x}", u32::from(c)),
+        c => f.write_char(c),
+    })?;
+    f.write_char('"')
+}
+
+/// Compact JSON, with no whitespace between tokens.
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Number(n) => write!(f, "{}", n),
+            Self::String(s) => write_string(f, s),
+            Self::Array(items) => {
+                f.write_char('[')?;
+                items.iter().enumerate().try_for_each(|(idx, item)| {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", item)
+                })?;
+                f.write_char(']')
+            }
+            Self::Object(fields) => {
+                f.write_char('{')?;
+                fields
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(idx, (key, value))| {
+                        if idx > 0 {
+                            f.write_char(',')?;
+                        }
+                        write_string(f, key)?;
+                        write!(f, ":{}", value)
+                    })?;
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+impl ToJson for Json {
+    fn to_json(&self) -> Json {
+        self.clone()
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Json {
+        Json::Bool(*self)
+    }
+}
+
+macro_rules! impl_to_json_for_ints {
+    ($($int:ty),*) => {
+        $(
+            impl ToJson for $int {
+                fn to_json(&self) -> Json {
+                    Json::Number((*self).try_into().expect("every integer fits in an `i128`"))
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_for_ints!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl ToJson for str {
+    fn to_json(&self) -> Json {
+        Json::String(self.to_owned())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Json {
+        self.as_str().to_json()
+    }
+}
+
+impl<T> ToJson for &T
+where
+    T: ToJson + ?Sized,
+{
+    fn to_json(&self) -> Json {
+        (**self).to_json()
+    }
+}
+
+impl<T> ToJson for Option<T>
+where
+    T: ToJson,
+{
+    fn to_json(&self) -> Json {
+        self.as_ref().map_or(Json::Null, ToJson::to_json)
+    }
+}
+
+impl<T> ToJson for [T]
+where
+    T: ToJson,
+{
+    fn to_json(&self) -> Json {
+        Json::array(self)
+    }
+}
+
+impl<T> ToJson for Vec<T>
+where
+    T: ToJson,
+{
+    fn to_json(&self) -> Json {
+        self.as_slice().to_json()
+    }
+}
+
+#[test]
+fn display() {
+    let json = Json::object([
+        ("null", Json::Null),
+        ("bool", true.to_json()),
+        ("numbers", vec![0u64, u64::MAX].to_json()),
+        ("negative", (-3i64).to_json()),
+        ("string", "say \"hi\"\n\u{7}".to_json()),
+        ("empty", Json::object::<&str>([])),
+        ("nested", Json::array([Some(1u8), None])),
+    ]);
+    assert_eq!(
+        json.to_string(),
+        r#"{"null":null,"bool":true,"numbers":[0,18446744073709551615],"negative":-3,"#.to_owned()
+            + r#""string":"say \"hi\"\n\u0007","empty":{},"nested":[1,null]}"#
+    );
+}
@@ -1,4 +1,38 @@
-#[cfg(test)]
+pub mod answers;
+pub mod batch;
+pub mod bench;
+/// Each day's solution, compiled only if its `day<N>` feature is enabled.
 pub mod days {
-    automod::dir!(pub "src/days");
+    #[cfg(feature = "day1")]
+    pub mod day1;
+    #[cfg(feature = "day2")]
+    pub mod day2;
+    #[cfg(feature = "day3")]
+    pub mod day3;
+    #[cfg(feature = "day4")]
+    pub mod day4;
+    #[cfg(feature = "day5")]
+    pub mod day5;
+    #[cfg(feature = "day6")]
+    pub mod day6;
+    #[cfg(feature = "day7")]
+    pub mod day7;
 }
+
+pub mod diagnostics;
+pub mod error;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod json;
+pub mod memory;
+pub mod parse;
+#[cfg(test)]
+mod reference;
+pub mod solution;
+pub mod trace;
+
+// Unit tests always count allocations, so that they can check what's counted.
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: memory::CountingAllocator = memory::CountingAllocator;
@@ -0,0 +1,164 @@
+//! Counting the heap allocations that parsing and solving make.
+//!
+//! Nothing is counted unless [`CountingAllocator`] is installed as the global allocator, which the
+//! `aoc2021` binary does when it's built with the `count-allocations` feature. The crate's own unit
+//! tests always install it, regardless of that feature, so that they can check the counts (e.g.,
+//! in [`crate::bench`]). Counts are kept per thread, so that other threads' allocations don't show
+//! up in a measurement.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Allocations made while running a measured closure.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Allocations {
+    /// The number of allocations, counting each reallocation as one.
+    pub count: usize,
+    /// The bytes requested by all of those allocations, counting only the growth of reallocations.
+    pub total_bytes: usize,
+    /// The most bytes in use at once, beyond those that were already in use before measuring.
+    pub peak_bytes: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Counters {
+    count: usize,
+    total_bytes: usize,
+    current_bytes: usize,
+    peak_bytes: usize,
+}
+
+thread_local! {
+    // `const` and without a destructor, so that accessing it never allocates.
+    static COUNTERS: Cell<Counters> = const {
+        Cell::new(Counters {
+            count: 0,
+            total_bytes: 0,
+            current_bytes: 0,
+            peak_bytes: 0,
+        })
+    };
+}
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+fn update(f: impl FnOnce(&mut Counters)) {
+    // Fails only while the thread is being torn down, when nothing is being measured anyway.
+    let _ = COUNTERS.try_with(|counters| {
+        let mut updated = counters.get();
+        f(&mut updated);
+        updated.peak_bytes = updated.peak_bytes.max(updated.current_bytes);
+        counters.set(updated);
+    });
+}
+
+/// Wraps [`System`], counting every allocation made through it.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            update(|counters| {
+                counters.count += 1;
+                counters.total_bytes += layout.size();
+                counters.current_bytes += layout.size();
+            });
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            update(|counters| {
+                counters.count += 1;
+                counters.total_bytes += layout.size();
+                counters.current_bytes += layout.size();
+            });
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        update(|counters| {
+            // Memory allocated on another thread may be freed on this one.
+            counters.current_bytes = counters.current_bytes.saturating_sub(layout.size());
+        });
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            update(|counters| {
+                counters.count += 1;
+                counters.total_bytes += new_size.saturating_sub(layout.size());
+                counters.current_bytes =
+                    (counters.current_bytes + new_size).saturating_sub(layout.size());
+            });
+        }
+        new_ptr
+    }
+}
+
+/// Whether [`CountingAllocator`] is the global allocator (or, strictly, whether it has allocated
+/// anything yet, which it has by the time `main` runs).
+pub fn is_counting() -> bool {
+    INSTALLED.load(Ordering::Relaxed)
+}
+
+/// Runs `f`, along with the allocations it made on this thread if [`CountingAllocator`] is the
+/// global allocator.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Option<Allocations>) {
+    let mut before = None;
+    update(|counters| {
+        before = Some(*counters);
+        counters.peak_bytes = counters.current_bytes;
+    });
+    let result = f();
+    let mut after = None;
+    update(|counters| after = Some(*counters));
+
+    let allocations = before
+        .zip(after)
+        .filter(|_| is_counting())
+        .map(|(before, after)| Allocations {
+            count: after.count - before.count,
+            total_bytes: after.total_bytes - before.total_bytes,
+            peak_bytes: after.peak_bytes.saturating_sub(before.current_bytes),
+        });
+    if let Some(before) = before {
+        // Restore the peak from before `f`, so that measurements can be nested.
+        update(|counters| counters.peak_bytes = counters.peak_bytes.max(before.peak_bytes));
+    }
+    (result, allocations)
+}
+
+#[test]
+fn measures_allocations() {
+    use std::hint::black_box;
+
+    assert!(is_counting());
+    let (sum, allocations) = measure(|| {
+        let small = black_box(vec![0u8; 100]);
+        drop(small);
+        let mut large = black_box(Vec::<u8>::with_capacity(1000));
+        large.extend([1; 2000]);
+        large.iter().map(|&b| usize::from(b)).sum::<usize>()
+    });
+    assert_eq!(sum, 2000);
+    let allocations = allocations.unwrap();
+    assert_eq!(allocations.count, 3);
+    assert!(allocations.total_bytes >= 2100);
+    assert!(allocations.peak_bytes >= 2000 && allocations.peak_bytes < 2100);
+
+    let (_, nothing) = measure(|| black_box(1 + 1));
+    assert_eq!(nothing, Some(Allocations::default()));
+}
@@ -0,0 +1,234 @@
+//! Building blocks for the shapes that puzzle inputs come in, reporting [`ParseError`]s that point
+//! into the original input.
+
+use crate::{
+    error::{ParseError, ReadError},
+    grid::Grid,
+    input::read_lines,
+};
+use std::{borrow::Cow, io::BufRead, str::FromStr};
+
+/// A day's puzzle input (or a piece of it, like a single line), which every error is reported
+/// against. Every `&str` passed to its methods must be a subslice of it.
+#[derive(Clone, Copy, Debug)]
+pub struct Source<'a> {
+    day: u8,
+    input: &'a str,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(day: u8, input: &'a str) -> Self {
+        Self { day, input }
+    }
+
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn error(&self, found: &str, expected: impl Into<Cow<'static, str>>) -> ParseError {
+        ParseError::new(self.day, self.input, found, expected)
+    }
+
+    /// An empty slice at the end of the input, for reporting input that ended too early.
+    pub fn end(&self) -> &'a str {
+        &self.input[self.input.len()..]
+    }
+
+    /// Every non-blank line, trimmed, with its 0-based line index in the input.
+    pub fn records(&self) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+        self.input
+            .lines()
+            .enumerate()
+            .map(|(idx, l)| (idx, l.trim()))
+            .filter(|(_idx, l)| !l.is_empty())
+    }
+
+    /// Runs of non-blank lines separated by blank lines. Each section includes the line break
+    /// after its last line, so that [`Source::end`] of a section points at the line after it.
+    pub fn sections(&self) -> impl Iterator<Item = &'a str> + 'a {
+        let input = self.input;
+        let mut rest = input;
+        std::iter::from_fn(move || {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                return None;
+            }
+            // Back up to the start of the line, so that leading whitespace stays in the section.
+            let rest_start = input.len() - rest.len();
+            let start = input[..rest_start].rfind('\n').map_or(0, |idx| idx + 1);
+
+            let mut end = start;
+            for l in input[start..].split_inclusive('\n') {
+                if l.trim().is_empty() {
+                    break;
+                }
+                end += l.len();
+            }
+            rest = &input[end..];
+            Some(&input[start..end])
+        })
+    }
+
+    pub fn parse<T>(&self, s: &str, expected: impl Into<Cow<'static, str>>) -> Result<T, ParseError>
+    where
+        T: FromStr,
+    {
+        s.parse().map_err(|_| self.error(s, expected))
+    }
+
+    /// Parses `s` as `expected` items separated by `delimiter`, ignoring whitespace around each
+    /// item.
+    pub fn list<T>(
+        &self,
+        s: &str,
+        delimiter: char,
+        expected: impl Into<Cow<'static, str>>,
+    ) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+    {
+        let expected = expected.into();
+        s.trim()
+            .split(delimiter)
+            .map(|item| self.parse(item.trim(), expected.clone()))
+            .collect()
+    }
+
+    /// Parses `s` as `expected` items separated by whitespace.
+    pub fn words<T>(
+        &self,
+        s: &str,
+        expected: impl Into<Cow<'static, str>>,
+    ) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+    {
+        let expected = expected.into();
+        s.split_whitespace()
+            .map(|item| self.parse(item, expected.clone()))
+            .collect()
+    }
+
+    /// Splits `s` into a key and a value around the first `delimiter`, pointing at all of `s` if
+    /// there isn't one.
+    pub fn pair(
+        &self,
+        s: &'a str,
+        delimiter: &str,
+        expected: impl Into<Cow<'static, str>>,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        s.split_once(delimiter)
+            .ok_or_else(|| self.error(s, expected))
+    }
+
+    /// Parses the lines of `section` as a grid of `num_columns` by `num_rows` whitespace-separated
+    /// `expected_tile`s, describing the grid as `what` (e.g., `"board"`) in errors.
+    pub fn fixed_grid<T>(
+        &self,
+        section: &'a str,
+        num_columns: usize,
+        num_rows: usize,
+        what: &str,
+        expected_tile: impl Into<Cow<'static, str>>,
+    ) -> Result<Grid<T>, ParseError>
+    where
+        T: FromStr,
+    {
+        let expected_tile = expected_tile.into();
+        let mut lines = section.lines().map(str::trim);
+        let rows = lines
+            .by_ref()
+            .take(num_rows)
+            .map(|l| {
+                let row = self.words(l, expected_tile.clone())?;
+                if row.len() != num_columns {
+                    return Err(self.error(l, format!("a {} row of {} numbers", what, num_columns)));
+                }
+                Ok(row)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if rows.len() < num_rows {
+            let end = &section[section.len()..];
+            return Err(self.error(end, format!("a {} of {} rows", what, num_rows)));
+        }
+        if let Some(extra) = lines.next() {
+            return Err(self.error(extra, format!("a blank line after a {}", what)));
+        }
+
+        Ok(Grid::from_rows(rows).expect("every row has `num_columns` tiles"))
+    }
+}
+
+/// Like [`Source::records`], but reads lines one at a time from `reader`, skipping blank lines.
+/// Lines aren't trimmed, so that errors in the trimmed line can be relocated with
+/// [`ParseError::within`].
+pub fn read_records(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(usize, String), ReadError>> {
+    read_lines(reader).filter(|res| match res {
+        Ok((_idx, line)) => !line.trim().is_empty(),
+        Err(_) => true,
+    })
+}
+
+#[test]
+fn records_and_sections() {
+    let input = "a\n\n  b \n c\n\n\nd";
+    let source = Source::new(0, input);
+    assert_eq!(
+        source.records().collect::<Vec<_>>(),
+        [(0, "a"), (2, "b"), (3, "c"), (6, "d")]
+    );
+    assert_eq!(
+        source.sections().collect::<Vec<_>>(),
+        ["a\n", "  b \n c\n", "d"]
+    );
+    assert_eq!(Source::new(0, "\n \n").sections().count(), 0);
+
+    let lines = read_records("a\n \nb\n".as_bytes())
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [(0, "a".to_owned()), (2, "b".to_owned())]);
+}
+
+#[test]
+fn lists_and_pairs() {
+    let input = "1, 2,3\nkey=value\n4 x";
+    let source = Source::new(0, input);
+    let (list, rest) = input.split_once('\n').unwrap();
+    assert_eq!(source.list::<u8>(list, ',', "a number").unwrap(), [1, 2, 3]);
+    let (pair, words) = rest.split_once('\n').unwrap();
+    assert_eq!(source.pair(pair, "=", "a pair").unwrap(), ("key", "value"));
+
+    let error = source.pair(words, "=", "a pair").unwrap_err();
+    assert_eq!((error.line, error.column, &*error.found), (3, 1, "4 x"));
+    let error = source.words::<u8>(words, "a number").unwrap_err();
+    assert_eq!((error.line, error.column, &*error.found), (3, 3, "x"));
+}
+
+#[test]
+fn fixed_grids() {
+    let input = "1 2\n3 4\n\n1 2\n3\n\n1 2\n";
+    let source = Source::new(0, input);
+    let sections = source.sections().collect::<Vec<_>>();
+    let grid = source
+        .fixed_grid::<u8>(sections[0], 2, 2, "board", "a number")
+        .unwrap();
+    assert_eq!(grid, Grid::from([[1, 2], [3, 4]]));
+
+    let error = source
+        .fixed_grid::<u8>(sections[1], 2, 2, "board", "a number")
+        .unwrap_err();
+    assert_eq!((error.line, error.column, &*error.found), (5, 1, "3"));
+    let error = source
+        .fixed_grid::<u8>(sections[2], 2, 2, "board", "a number")
+        .unwrap_err();
+    assert_eq!((error.line, error.column, &*error.found), (8, 1, ""));
+    let error = source
+        .fixed_grid::<u8>(sections[0], 2, 1, "board", "a number")
+        .unwrap_err();
+    assert_eq!(
+        (error.line, &*error.expected),
+        (2, "a blank line after a board")
+    );
+}
@@ -0,0 +1,311 @@
+//! Naive, obviously-correct solvers for every day, which parse and solve straight from the puzzle
+//! text without any of the tricks the real solvers use. They're only for checking the real
+//! solvers against on generated inputs.
+
+// Each day's reference solver is only checked if that day's feature is enabled.
+#![cfg_attr(
+    not(all(
+        feature = "day1",
+        feature = "day2",
+        feature = "day3",
+        feature = "day4",
+        feature = "day5",
+        feature = "day6",
+        feature = "day7"
+    )),
+    allow(dead_code, unused_imports)
+)]
+
+use crate::{
+    generate,
+    solution::{self, Part},
+};
+use std::collections::HashMap;
+
+fn numbers(input: &str, separator: char) -> Vec<i64> {
+    input
+        .split(separator)
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(|n| n.parse().unwrap())
+        .collect()
+}
+
+fn day1(input: &str, part: Part) -> i64 {
+    let depths = numbers(input, '\n');
+    let window = match part {
+        Part::One => 1,
+        Part::Two => 3,
+    };
+    let sums = depths
+        .windows(window)
+        .map(|w| w.iter().sum::<i64>())
+        .collect::<Vec<_>>();
+    let mut increases = 0;
+    for idx in 1..sums.len() {
+        if sums[idx] > sums[idx - 1] {
+            increases += 1;
+        }
+    }
+    increases
+}
+
+fn day2(input: &str, part: Part) -> i64 {
+    let (mut horizontal_pos, mut depth, mut aim) = (0, 0, 0);
+    for l in input.lines() {
+        let (command, distance) = l.split_once(' ').unwrap();
+        let distance = distance.parse::<i64>().unwrap();
+        match (command, part) {
+            ("forward", Part::One) => horizontal_pos += distance,
+            ("down", Part::One) => depth += distance,
+            ("up", Part::One) => depth -= distance,
+            ("forward", Part::Two) => {
+                horizontal_pos += distance;
+                depth += aim * distance;
+            }
+            ("down", Part::Two) => aim += distance,
+            ("up", Part::Two) => aim -= distance,
+            _ => panic!("unrecognized command {:?}", l),
+        }
+    }
+    horizontal_pos * depth
+}
+
+fn day3(input: &str, part: Part) -> i64 {
+    let samples = input.lines().collect::<Vec<_>>();
+    let width = samples[0].len();
+    let ones_at =
+        |samples: &[&str], idx| samples.iter().filter(|s| s.as_bytes()[idx] == b'1').count();
+    let to_number = |bits: &str| i64::from_str_radix(bits, 2).unwrap();
+
+    match part {
+        Part::One => {
+            let gamma = (0..width)
+                .map(|idx| {
+                    if ones_at(&samples, idx) * 2 > samples.len() {
+                        '1'
+                    } else {
+                        '0'
+                    }
+                })
+                .collect::<String>();
+            let epsilon = gamma
+                .chars()
+                .map(|c| if c == '1' { '0' } else { '1' })
+                .collect::<String>();
+            to_number(&gamma) * to_number(&epsilon)
+        }
+        Part::Two => {
+            let rating = |keep_most_common: bool| {
+                let mut candidates = samples.clone();
+                let mut idx = 0;
+                while candidates.len() > 1 {
+                    let ones = ones_at(&candidates, idx);
+                    let zeros = candidates.len() - ones;
+                    let keep = match (keep_most_common, ones >= zeros) {
+                        (true, true) | (false, false) => b'1',
+                        (true, false) | (false, true) => b'0',
+                    };
+                    candidates.retain(|s| s.as_bytes()[idx] == keep);
+                    idx += 1;
+                }
+                to_number(candidates[0])
+            };
+            rating(true) * rating(false)
+        }
+    }
+}
+
+fn day4(input: &str, part: Part) -> i64 {
+    let mut sections = input.split("\n\n");
+    let draws = numbers(sections.next().unwrap(), ',');
+    let boards = sections
+        .map(|board| {
+            board
+                .lines()
+                .map(|row| numbers(row, ' '))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut marked = vec![[[false; 5]; 5]; boards.len()];
+    let mut won = vec![false; boards.len()];
+    let mut scores = Vec::new();
+    for &draw in &draws {
+        for (board_idx, board) in boards.iter().enumerate() {
+            if won[board_idx] {
+                continue;
+            }
+            for row in 0..5 {
+                for column in 0..5 {
+                    if board[row][column] == draw {
+                        marked[board_idx][row][column] = true;
+                    }
+                }
+            }
+            let marks = &marked[board_idx];
+            let full_row = (0..5).any(|row| (0..5).all(|column| marks[row][column]));
+            let full_column = (0..5).any(|column| (0..5).all(|row| marks[row][column]));
+            if full_row || full_column {
+                won[board_idx] = true;
+                let mut unmarked_sum = 0;
+                for row in 0..5 {
+                    for column in 0..5 {
+                        if !marks[row][column] {
+                            unmarked_sum += board[row][column];
+                        }
+                    }
+                }
+                scores.push(unmarked_sum * draw);
+            }
+        }
+    }
+    match part {
+        Part::One => scores[0],
+        Part::Two => *scores.last().unwrap(),
+    }
+}
+
+fn day5(input: &str, part: Part) -> i64 {
+    let mut tiles = HashMap::<(i64, i64), i64>::new();
+    for l in input.lines() {
+        let (from, to) = l.split_once(" -> ").unwrap();
+        let (x1, y1) = from.split_once(',').unwrap();
+        let (x2, y2) = to.split_once(',').unwrap();
+        let [x1, y1, x2, y2] = [x1, y1, x2, y2].map(|n| n.parse::<i64>().unwrap());
+        if part == Part::One && x1 != x2 && y1 != y2 {
+            continue;
+        }
+        let steps = (x2 - x1).abs().max((y2 - y1).abs());
+        for step in 0..=steps {
+            let tile = (
+                x1 + step * (x2 - x1).signum(),
+                y1 + step * (y2 - y1).signum(),
+            );
+            *tiles.entry(tile).or_default() += 1;
+        }
+    }
+    tiles.values().filter(|&&count| count >= 2).count() as i64
+}
+
+/// Simulates every fish individually, which only finishes in reasonable time for a few dozen
+/// days.
+fn day6(input: &str, days: usize) -> i64 {
+    let mut fish = numbers(input, ',');
+    for _ in 0..days {
+        let mut babies = 0;
+        for timer in &mut fish {
+            if *timer == 0 {
+                *timer = 6;
+                babies += 1;
+            } else {
+                *timer -= 1;
+            }
+        }
+        fish.extend((0..babies).map(|_| 8));
+    }
+    fish.len() as i64
+}
+
+fn day7(input: &str, part: Part) -> i64 {
+    let positions = numbers(input, ',');
+    let cost = |distance: i64| match part {
+        Part::One => distance,
+        Part::Two => (1..=distance).sum(),
+    };
+    let (&min, &max) = (
+        positions.iter().min().unwrap(),
+        positions.iter().max().unwrap(),
+    );
+    (min..=max)
+        .map(|target| {
+            positions
+                .iter()
+                .map(|&position| cost((position - target).abs()))
+                .sum::<i64>()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Checks the real solvers against the reference solvers over inputs of each of `sizes`,
+/// generated from `seeds` different seeds.
+fn check_day(day: u8, sizes: &[usize], seeds: u64, reference: fn(&str, Part) -> i64) {
+    let solution = solution::find(day).unwrap();
+    sizes.iter().for_each(|&size| {
+        (0..seeds).for_each(|seed| {
+            let input = generate::generate(day, seed, size).unwrap();
+            Part::ALL.into_iter().for_each(|part| {
+                assert_eq!(
+                    solution.run(&input, part).unwrap(),
+                    reference(&input, part).to_string(),
+                    "day {} part {}, seed {}, size {}:\n{}",
+                    day,
+                    part,
+                    seed,
+                    size,
+                    input
+                );
+            });
+        });
+    });
+}
+
+#[test]
+#[cfg(feature = "day1")]
+fn day1_matches_reference() {
+    check_day(1, &[0, 1, 3, 4, 50, 1000], 20, day1);
+}
+
+#[test]
+#[cfg(feature = "day2")]
+fn day2_matches_reference() {
+    check_day(2, &[0, 1, 10, 500], 20, day2);
+}
+
+#[test]
+#[cfg(feature = "day3")]
+fn day3_matches_reference() {
+    check_day(3, &[3, 5, 64, 301], 20, day3);
+}
+
+#[test]
+#[cfg(feature = "day4")]
+fn day4_matches_reference() {
+    check_day(4, &[1, 2, 10, 60], 10, day4);
+}
+
+#[test]
+#[cfg(feature = "day5")]
+fn day5_matches_reference() {
+    check_day(5, &[1, 10, 100], 3, day5);
+}
+
+#[test]
+#[cfg(feature = "day6")]
+fn day6_matches_reference() {
+    use crate::{days::day6, generate::Rng};
+
+    (0..20).for_each(|seed| {
+        let mut rng = Rng::new(seed);
+        let count = rng.between(1, 30) as usize;
+        let input = generate::lanternfish(&mut rng, count);
+        let pool = day6::parse_initial_pool(&input).unwrap();
+        [0, 1, 6, 7, 8, 9, 18, 30, 60].into_iter().for_each(|days| {
+            assert_eq!(
+                day6::run_simulation(&pool, days).unwrap() as i64,
+                day6(&input, days),
+                "seed {}, {} days: {}",
+                seed,
+                days,
+                input
+            );
+        });
+    });
+}
+
+#[test]
+#[cfg(feature = "day7")]
+fn day7_matches_reference() {
+    check_day(7, &[1, 2, 10, 100], 3, day7);
+}
@@ -0,0 +1,237 @@
+use crate::{
+    input::normalize,
+    json::{Json, ToJson},
+};
+use anyhow::{anyhow, bail, Context};
+use std::{
+    any::Any,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Self; 2] = [Self::One, Self::Two];
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::One => f.pad("1"),
+            Self::Two => f.pad("2"),
+        }
+    }
+}
+
+impl ToJson for Part {
+    fn to_json(&self) -> Json {
+        match self {
+            Self::One => 1.to_json(),
+            Self::Two => 2.to_json(),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self::One),
+            "2" => Ok(Self::Two),
+            unrecognized => bail!("expected part `1` or `2`, got {:?}", unrecognized),
+        }
+    }
+}
+
+/// A day's puzzle: one parser shared by both parts, and an entry point for each part.
+pub trait Solution {
+    const DAY: u8;
+
+    type Parsed: 'static;
+    type Part1: Display + ToJson;
+    type Part2: Display + ToJson;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Parsed>;
+
+    fn part1(parsed: &Self::Parsed) -> anyhow::Result<Self::Part1>;
+
+    fn part2(parsed: &Self::Parsed) -> anyhow::Result<Self::Part2>;
+
+    /// The intermediate results behind the answer to `part`, for tools to consume.
+    fn details(parsed: &Self::Parsed, part: Part) -> anyhow::Result<Json>;
+}
+
+/// An object-safe view of a [`Solution`], so that days with different parsed and answer types can
+/// live side by side in [`SOLUTIONS`].
+pub trait DynSolution: Sync {
+    fn day(&self) -> u8;
+
+    /// Parses `input` after [`normalize`]-ing it, so that no day has to handle byte order marks,
+    /// CRLF line breaks, tabs, or trailing blank lines itself.
+    fn parse(&self, input: &str) -> anyhow::Result<Box<dyn Any>>;
+
+    fn solve(&self, parsed: &dyn Any, part: Part) -> anyhow::Result<String>;
+
+    fn run(&self, input: &str, part: Part) -> anyhow::Result<String> {
+        self.solve(&*self.parse(input)?, part)
+    }
+
+    /// Like [`DynSolution::solve`], but reports the answer alongside the intermediate results
+    /// behind it, as a JSON object with `day`, `part`, `answer`, and `details` fields.
+    fn report(&self, parsed: &dyn Any, part: Part) -> anyhow::Result<Json>;
+}
+
+fn downcast<S>(parsed: &dyn Any) -> anyhow::Result<&S::Parsed>
+where
+    S: Solution,
+{
+    parsed
+        .downcast_ref::<S::Parsed>()
+        .with_context(|| anyhow!("parsed input was not produced by day {}", S::DAY))
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution + Sync,
+{
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Box<dyn Any>> {
+        Ok(Box::new(S::parse(&normalize(input))?))
+    }
+
+    fn solve(&self, parsed: &dyn Any, part: Part) -> anyhow::Result<String> {
+        let parsed = downcast::<S>(parsed)?;
+        Ok(match part {
+            Part::One => S::part1(parsed)?.to_string(),
+            Part::Two => S::part2(parsed)?.to_string(),
+        })
+    }
+
+    fn report(&self, parsed: &dyn Any, part: Part) -> anyhow::Result<Json> {
+        let parsed = downcast::<S>(parsed)?;
+        let answer = match part {
+            Part::One => S::part1(parsed)?.to_json(),
+            Part::Two => S::part2(parsed)?.to_json(),
+        };
+        Ok(Json::object([
+            ("day", S::DAY.to_json()),
+            ("part", part.to_json()),
+            ("answer", answer),
+            ("details", S::details(parsed, part)?),
+        ]))
+    }
+}
+
+/// Every day in [`crate::days`] whose feature is enabled, ordered by day.
+///
+/// This list is maintained by hand rather than discovered from the modules, so a new day's
+/// `Solver` has to be added here (behind its feature) as well as to [`crate::days`].
+pub static SOLUTIONS: &[&dyn DynSolution] = &[
+    #[cfg(feature = "day1")]
+    &crate::days::day1::Solver,
+    #[cfg(feature = "day2")]
+    &crate::days::day2::Solver,
+    #[cfg(feature = "day3")]
+    &crate::days::day3::Solver,
+    #[cfg(feature = "day4")]
+    &crate::days::day4::Solver,
+    #[cfg(feature = "day5")]
+    &crate::days::day5::Solver,
+    #[cfg(feature = "day6")]
+    &crate::days::day6::Solver,
+    #[cfg(feature = "day7")]
+    &crate::days::day7::Solver,
+];
+
+pub fn find(day: u8) -> Option<&'static dyn DynSolution> {
+    SOLUTIONS
+        .iter()
+        .copied()
+        .find(|solution| solution.day() == day)
+}
+
+#[test]
+fn registry_is_ordered_by_day() {
+    assert!(SOLUTIONS
+        .windows(2)
+        .all(|pair| pair[0].day() < pair[1].day()));
+}
+
+#[test]
+fn registry_solves_bundled_inputs() {
+    let expected: [(u8, &str, [&str; 2]); 7] = [
+        (1, include_str!("days/day1_input.txt"), ["1288", "1311"]),
+        (
+            2,
+            include_str!("days/day2_input.txt"),
+            ["2322630", "2105273490"],
+        ),
+        (
+            3,
+            include_str!("days/day3_input.txt"),
+            ["2003336", "1877139"],
+        ),
+        (4, include_str!("days/day4_input.txt"), ["45031", "2568"]),
+        (5, include_str!("days/day5_input.txt"), ["4728", "17717"]),
+        (
+            6,
+            include_str!("days/day6_input.txt"),
+            ["372984", "1681503251694"],
+        ),
+        (
+            7,
+            include_str!("days/day7_input.txt"),
+            ["364898", "104149091"],
+        ),
+    ];
+    for (day, input, answers) in expected {
+        let Some(solution) = find(day) else {
+            continue;
+        };
+        let parsed = solution.parse(input).unwrap();
+        let messy = solution.parse(&crate::input::messy(input)).unwrap();
+        for (part, answer) in Part::ALL.into_iter().zip(answers) {
+            assert_eq!(solution.solve(&*parsed, part).unwrap(), answer);
+            assert_eq!(solution.solve(&*messy, part).unwrap(), answer);
+        }
+    }
+}
+
+#[test]
+#[cfg(all(feature = "day3", feature = "day7"))]
+fn reports_details() {
+    let solution = find(7).unwrap();
+    let parsed = solution.parse("16,1,2,0,4,2,7,1,2,14").unwrap();
+    assert_eq!(
+        solution.report(&*parsed, Part::Two).unwrap().to_string(),
+        r#"{"day":7,"part":2,"answer":168,"details":{"fuel":168,"positions":[5]}}"#,
+    );
+
+    let solution = find(3).unwrap();
+    let parsed = solution.parse("00100\n11110\n10110\n10111\n").unwrap();
+    assert_eq!(
+        solution.report(&*parsed, Part::One).unwrap().to_string(),
+        r#"{"day":3,"part":1,"answer":198,"details":{"#.to_owned()
+            + r#""gamma":{"bits":"10110","value":22},"epsilon":{"bits":"01001","value":9}}}"#,
+    );
+    // Ratings are indexed among the samples, which skips the blank line.
+    let parsed = solution
+        .parse("00100\n\n11110\n10110\n10111\n01010\n")
+        .unwrap();
+    assert_eq!(
+        solution.report(&*parsed, Part::Two).unwrap().to_string(),
+        r#"{"day":3,"part":2,"answer":92,"details":{"o2_generator_rating":{"sample_idx":3,"#
+            .to_owned()
+            + r#""sample":{"bits":"10111","value":23}},"co2_scrubber_rating":{"sample_idx":0,"#
+            + r#""sample":{"bits":"00100","value":4}}}}"#,
+    );
+}
@@ -0,0 +1,322 @@
+//! Events that solvers report as they work, for following along with how they reach an answer.
+//!
+//! Nothing is reported unless an [`Observer`] is installed with [`observe`], which only affects
+//! the current thread.
+
+use crate::json::{Json, ToJson};
+use std::{
+    cell::RefCell,
+    fmt::{self, Display, Formatter},
+    io::Write,
+    rc::Rc,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event {
+    /// Day 4: the `number_idx`th number was drawn.
+    Draw { number_idx: usize, number: u8 },
+    /// Day 4: a player's board won for the first time.
+    BoardWon {
+        player_idx: usize,
+        unmarked_sum: u32,
+    },
+    /// Day 3: a bit criteria kept only the candidates with `kept` at bit `bit_idx`.
+    Elimination {
+        bit_idx: usize,
+        kept: bool,
+        remaining: usize,
+    },
+    /// Day 6: the population after simulating `days_simulated` days.
+    SimulationStep {
+        days_simulated: usize,
+        population: usize,
+    },
+    /// Day 7: the fuel it would cost to align every crab submarine to `position`.
+    Candidate { position: u32, fuel: u64 },
+}
+
+impl Event {
+    pub fn day(&self) -> u8 {
+        match self {
+            Self::Draw { .. } | Self::BoardWon { .. } => 4,
+            Self::Elimination { .. } => 3,
+            Self::SimulationStep { .. } => 6,
+            Self::Candidate { .. } => 7,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Draw { .. } => "draw",
+            Self::BoardWon { .. } => "board_won",
+            Self::Elimination { .. } => "elimination",
+            Self::SimulationStep { .. } => "simulation_step",
+            Self::Candidate { .. } => "candidate",
+        }
+    }
+}
+
+/// An object with the event's day and name, followed by its fields in declaration order.
+impl ToJson for Event {
+    fn to_json(&self) -> Json {
+        let fields = match *self {
+            Self::Draw { number_idx, number } => vec![
+                ("number_idx", number_idx.to_json()),
+                ("number", number.to_json()),
+            ],
+            Self::BoardWon {
+                player_idx,
+                unmarked_sum,
+            } => vec![
+                ("player_idx", player_idx.to_json()),
+                ("unmarked_sum", unmarked_sum.to_json()),
+            ],
+            Self::Elimination {
+                bit_idx,
+                kept,
+                remaining,
+            } => vec![
+                ("bit_idx", bit_idx.to_json()),
+                ("kept", kept.to_json()),
+                ("remaining", remaining.to_json()),
+            ],
+            Self::SimulationStep {
+                days_simulated,
+                population,
+            } => vec![
+                ("days_simulated", days_simulated.to_json()),
+                ("population", population.to_json()),
+            ],
+            Self::Candidate { position, fuel } => {
+                vec![("position", position.to_json()), ("fuel", fuel.to_json())]
+            }
+        };
+        Json::object(
+            [
+                ("day", self.day().to_json()),
+                ("event", self.name().to_json()),
+            ]
+            .into_iter()
+            .chain(fields),
+        )
+    }
+}
+
+/// A human-readable, one-line description of the event.
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}: ", self.day())?;
+        match *self {
+            Self::Draw { number_idx, number } => write!(f, "drew {} (#{})", number, number_idx),
+            Self::BoardWon {
+                player_idx,
+                unmarked_sum,
+            } => write!(
+                f,
+                "player {} won with unmarked numbers summing to {}",
+                player_idx, unmarked_sum
+            ),
+            Self::Elimination {
+                bit_idx,
+                kept,
+                remaining,
+            } => write!(
+                f,
+                "kept samples with bit {} set to {}, {} remaining",
+                bit_idx,
+                u8::from(kept),
+                remaining
+            ),
+            Self::SimulationStep {
+                days_simulated,
+                population,
+            } => write!(
+                f,
+                "{} lanternfish after {} days",
+                population, days_simulated
+            ),
+            Self::Candidate { position, fuel } => {
+                write!(f, "aligning to {} costs {} fuel", position, fuel)
+            }
+        }
+    }
+}
+
+pub trait Observer {
+    fn event(&mut self, event: &Event);
+}
+
+/// Writes each event's [`Display`] form on its own line. Write errors are ignored, since tracing
+/// shouldn't stop a solver.
+pub struct HumanTrace<W>(pub W);
+
+impl<W> Observer for HumanTrace<W>
+where
+    W: Write,
+{
+    fn event(&mut self, event: &Event) {
+        let _ = writeln!(self.0, "{}", event);
+    }
+}
+
+/// Writes each event as a JSON object on its own line, like:
+///
+/// ```text
+/// {"day":4,"event":"draw","number_idx":0,"number":7}
+/// ```
+///
+/// Write errors are ignored, since tracing shouldn't stop a solver.
+pub struct JsonLines<W>(pub W);
+
+impl<W> Observer for JsonLines<W>
+where
+    W: Write,
+{
+    fn event(&mut self, event: &Event) {
+        let _ = writeln!(self.0, "{}", event.to_json());
+    }
+}
+
+/// Keeps every event, for inspecting them after the fact. Clones share the same events.
+#[derive(Clone, Debug, Default)]
+pub struct Recorder(Rc<RefCell<Vec<Event>>>);
+
+impl Recorder {
+    pub fn events(&self) -> Vec<Event> {
+        self.0.borrow().clone()
+    }
+}
+
+impl Observer for Recorder {
+    fn event(&mut self, event: &Event) {
+        self.0.borrow_mut().push(*event);
+    }
+}
+
+thread_local! {
+    static OBSERVER: RefCell<Option<Box<dyn Observer>>> = RefCell::new(None);
+}
+
+/// Reports every event emitted on this thread while running `f` to `observer`, restoring the
+/// previous observer (if any) afterwards.
+pub fn observe<R>(observer: impl Observer + 'static, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Box<dyn Observer>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            OBSERVER.with(|observer| *observer.borrow_mut() = previous);
+        }
+    }
+
+    let previous = OBSERVER.with(|current| current.borrow_mut().replace(Box::new(observer)));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Reports the event `make_event` returns to the current observer. `make_event` isn't called
+/// if there's no observer.
+#[cfg_attr(
+    not(any(feature = "day3", feature = "day4", feature = "day6", feature = "day7")),
+    allow(dead_code)
+)]
+pub(crate) fn emit(make_event: impl FnOnce() -> Event) {
+    OBSERVER.with(|observer| {
+        if let Some(observer) = &mut *observer.borrow_mut() {
+            observer.event(&make_event());
+        }
+    });
+}
+
+#[test]
+fn observers() {
+    let recorder = Recorder::default();
+    emit(|| unreachable!("nobody is observing"));
+    observe(recorder.clone(), || {
+        emit(|| Event::Draw {
+            number_idx: 0,
+            number: 7,
+        });
+        let nested = Recorder::default();
+        observe(nested.clone(), || {
+            emit(|| Event::Candidate {
+                position: 2,
+                fuel: 37,
+            })
+        });
+        assert_eq!(nested.events().len(), 1);
+        emit(|| Event::Elimination {
+            bit_idx: 1,
+            kept: true,
+            remaining: 3,
+        });
+        emit(|| Event::SimulationStep {
+            days_simulated: 18,
+            population: 26,
+        });
+    });
+    emit(|| unreachable!("nobody is observing anymore"));
+    let events = recorder.events();
+    assert_eq!(events.len(), 3);
+
+    assert_eq!(events[0].to_string(), "day 4: drew 7 (#0)");
+    let mut json = Vec::new();
+    events
+        .iter()
+        .for_each(|event| JsonLines(&mut json).event(event));
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        "\
+{\"day\":4,\"event\":\"draw\",\"number_idx\":0,\"number\":7}
+{\"day\":3,\"event\":\"elimination\",\"bit_idx\":1,\"kept\":true,\"remaining\":3}
+{\"day\":6,\"event\":\"simulation_step\",\"days_simulated\":18,\"population\":26}
+"
+    );
+}
+
+#[test]
+#[cfg(all(feature = "day4", feature = "day6"))]
+fn solvers_emit_events() {
+    use crate::{
+        input::InputProvider,
+        solution::{self, Part},
+    };
+
+    let events = |day, input: &str| {
+        let recorder = Recorder::default();
+        observe(recorder.clone(), || {
+            let solution = solution::find(day).unwrap();
+            solution.run(input, Part::One).unwrap()
+        });
+        recorder.events()
+    };
+
+    let day4 = events(4, &InputProvider::bundled().load(4).unwrap());
+    assert_eq!(
+        day4[..2],
+        [
+            Event::Draw {
+                number_idx: 0,
+                number: 0
+            },
+            Event::Draw {
+                number_idx: 1,
+                number: 56
+            },
+        ]
+    );
+    assert!(day4.contains(&Event::BoardWon {
+        player_idx: 45,
+        unmarked_sum: 919
+    }));
+
+    let day6 = events(6, "3,4,3,1,2");
+    assert_eq!(day6.len(), 80);
+    assert_eq!(
+        day6[17],
+        Event::SimulationStep {
+            days_simulated: 18,
+            population: 26
+        }
+    );
+}